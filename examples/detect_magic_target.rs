@@ -2,8 +2,9 @@ use std::thread::sleep;
 use std::time::Duration;
 
 const MAX_FRAME_LEN: usize = 264;
-const UNLOCK_1: [u8; 1] = [0x40];
-const UNLOCK_2: [u8; 1] = [0x40];
+const HLTA: [u8; 2] = [0x50, 0x00];
+const UNLOCK_1: u8 = 0x40;
+const UNLOCK_2: [u8; 1] = [0x43];
 
 fn main() -> nfc1::Result<()> {
 	let mut context = nfc1::Context::new()?;
@@ -11,23 +12,21 @@ fn main() -> nfc1::Result<()> {
 	print!("NFC reader: {} opened\n\n", device.name());
 	device.initiator_init()?;
 
-	// Configure the CRC
-	device.set_property_bool(nfc1::Property::HandleCrc, false)?;
-	// Use raw send/receive methods
-	device.set_property_bool(nfc1::Property::EasyFraming, false)?;
-	// Disable 14443-4 autoswitching
-	device.set_property_bool(nfc1::Property::AutoIso144434, false)?;
+	// Disable CRC handling, easy framing and 14443-4 autoswitching
+	let mut session = nfc1::RawSession::new(&mut device)?;
 
 	loop {
 		println!("Looking for targets...\n");
-		match device.initiator_select_passive_target(&nfc1::Modulation{
+		match session.device().initiator_select_passive_target(&nfc1::Modulation{
 			modulation_type: nfc1::ModulationType::Iso14443a,
 			baud_rate: nfc1::BaudRate::Baud106,
 		}) {
 			Ok(target) => {
-				print!("Target found: {}", target.to_string(false)?);
-				match device.initiator_transceive_bits(&UNLOCK_1, 7, MAX_FRAME_LEN) {
-					Ok(rx) => print!("Received bits: {:02X?}\n", rx),
+				print!("Target found: {}", target);
+				// Magic cards only accept the unlock commands once halted. HLTA is not answered.
+				let _ = session.transceive(&nfc1::RawFrame::standard(&HLTA), MAX_FRAME_LEN);
+				match session.transceive(&nfc1::RawFrame::short(UNLOCK_1), MAX_FRAME_LEN) {
+					Ok(rx) => print!("Received bits: {:02X?}\n", rx.data),
 					Err(err) => {
 						print!("This is NOT a backdoored rewritable UID chinese card ({:?})\n", err);
 						sleep(Duration::from_secs(5));
@@ -36,9 +35,9 @@ fn main() -> nfc1::Result<()> {
 					},
 				};
 
-				match device.initiator_transceive_bytes(&UNLOCK_2, MAX_FRAME_LEN, nfc1::Timeout::Default){
+				match session.transceive(&nfc1::RawFrame::without_crc(&UNLOCK_2), MAX_FRAME_LEN) {
 					Ok(rx) => {
-						print!("Received bytes: {:02X?}\n", rx);
+						print!("Received bytes: {:02X?}\n", rx.data);
 						print!("This is a backdoored rewritable UID chinese card\n")
 					},
 					Err(err) => print!("This is NOT a backdoored rewritable UID chinese card ({:?})\n", err),
@@ -48,4 +47,4 @@ fn main() -> nfc1::Result<()> {
 		}
 		sleep(Duration::from_secs(5));
 	}
-}
\ No newline at end of file
+}
@@ -0,0 +1,109 @@
+//! Protocol info of the answer to request of ISO/IEC 14443B targets (ATQB).
+
+use crate::ats::{bit_rates, waiting_time, FRAME_SIZES};
+use crate::target_info::Iso14443b;
+use crate::{BaudRate, ParseError};
+use std::time::Duration;
+
+/// Protocol info of an ATQB, as found in `Iso14443b::protocol_info` (see ISO/IEC 14443-3 7.9.3)
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProtocolInfo {
+	/// Bit rate capability, coded like TA(1) of an ATS
+	pub bit_rate_capability: u8,
+	/// Max frame size code (FSCI)
+	pub max_frame_size: u8,
+	/// Protocol type, the low nibble of the second byte
+	pub protocol_type: u8,
+	/// Frame waiting time integer
+	pub fwi: u8,
+	/// Application data coding
+	pub adc: u8,
+	/// Frame options: NAD and CID support
+	pub fo: u8,
+	/// Start-up frame guard time integer, only in an extended ATQB
+	pub sfgi: Option<u8>,
+}
+
+impl ProtocolInfo {
+	/// Parses the protocol info of an ATQB, 3 bytes or 4 bytes for an extended ATQB
+	pub fn parse(protocol_info: &[u8]) -> Result<Self, ParseError> {
+		let [bit_rate_capability, frame_size, timing, rest @ ..] = protocol_info else {
+			return Err(ParseError::Truncated{ expected: 3, len: protocol_info.len() });
+		};
+		let mut parsed = Self::from([*bit_rate_capability, *frame_size, *timing]);
+		parsed.sfgi = rest.first().map(|extended| extended >> 4);
+		Ok(parsed)
+	}
+
+	/// Returns whether the same bit rate must be used in both directions
+	pub fn same_bit_rate_required(&self) -> bool {
+		self.bit_rate_capability & 0x80 != 0
+	}
+
+	/// Returns the bit rates supported from card to reader
+	pub fn picc_to_pcd_bit_rates(&self) -> Vec<BaudRate> {
+		bit_rates(self.bit_rate_capability >> 4)
+	}
+
+	/// Returns the bit rates supported from reader to card
+	pub fn pcd_to_picc_bit_rates(&self) -> Vec<BaudRate> {
+		bit_rates(self.bit_rate_capability)
+	}
+
+	/// Returns the maximum frame size the card accepts (FSC), in bytes
+	pub fn fsc(&self) -> u16 {
+		FRAME_SIZES[(self.max_frame_size as usize).min(FRAME_SIZES.len() - 1)]
+	}
+
+	pub fn iso14443_4_compliant(&self) -> bool {
+		self.protocol_type & 0x01 != 0
+	}
+
+	/// Returns whether the application data is coded as AFI, CRC_B of the AID and number of applications
+	pub fn application_data_coded(&self) -> bool {
+		self.adc & 0x01 != 0
+	}
+
+	pub fn nad_supported(&self) -> bool {
+		self.fo & 0x02 != 0
+	}
+
+	pub fn cid_supported(&self) -> bool {
+		self.fo & 0x01 != 0
+	}
+
+	/// Returns the frame waiting time (FWT), with the RFU FWI 15 treated as 4
+	pub fn fwt(&self) -> Duration {
+		waiting_time(if self.fwi == 15 { 4 } else { self.fwi })
+	}
+
+	/// Returns the start-up frame guard time (SFGT), or `None` if no guard time is required
+	pub fn sfgt(&self) -> Option<Duration> {
+		match self.sfgi {
+			None | Some(0) | Some(15) => None,
+			Some(sfgi) => Some(waiting_time(sfgi)),
+		}
+	}
+}
+
+impl From<[u8; 3]> for ProtocolInfo {
+	fn from([bit_rate_capability, frame_size, timing]: [u8; 3]) -> Self {
+		Self{
+			bit_rate_capability,
+			max_frame_size: frame_size >> 4,
+			protocol_type: frame_size & 0x0f,
+			fwi: timing >> 4,
+			adc: (timing >> 2) & 0x03,
+			fo: timing & 0x03,
+			sfgi: None,
+		}
+	}
+}
+
+impl Iso14443b {
+	/// Decodes `protocol_info`
+	pub fn decode_protocol_info(&self) -> ProtocolInfo {
+		self.protocol_info.into()
+	}
+}
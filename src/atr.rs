@@ -0,0 +1,394 @@
+//! Answer to reset (ATR) as defined in ISO/IEC 7816-3 (8.2).
+//!
+//! ISO/IEC 14443B' targets report an ATR, and PC/SC readers report one for
+//! contactless cards too; `Target::pcsc_atr` synthesizes the same pseudo-ATR
+//! as a PC/SC reader (PC/SC part 3, 3.1.3.2.3).
+
+use crate::ats::Ats;
+use crate::historical::{HistoricalBytes, PCSC_RID};
+use crate::redact::Redacted;
+use crate::target_info::{BuildError, Iso14443bi, TargetInfo};
+use crate::{ParseError, Target};
+use std::fmt;
+
+const TS_DIRECT: u8 = 0x3b;
+const TS_INVERSE: u8 = 0x3f;
+
+/// Longest ATR allowed by ISO/IEC 7816-3, including TS
+const MAX_LEN: usize = 33;
+
+// Clock rate conversion integer Fi and baud rate adjustment integer Di, by their code in TA1
+const FI: [Option<u16>; 16] = [Some(372), Some(372), Some(558), Some(744), Some(1116), Some(1488), Some(1860), None, None, Some(512), Some(768), Some(1024), Some(1536), Some(2048), None, None];
+const DI: [Option<u8>; 16] = [None, Some(1), Some(2), Some(4), Some(8), Some(16), Some(32), Some(64), Some(12), Some(20), None, None, None, None, None, None];
+
+/// Interface bytes TAi, TBi, TCi and TDi
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InterfaceBytes {
+	pub ta: Option<u8>,
+	pub tb: Option<u8>,
+	pub tc: Option<u8>,
+	/// Presence of the next interface bytes and the protocol they apply to
+	pub td: Option<u8>,
+}
+
+impl InterfaceBytes {
+	// Presence bits of these bytes, as in T0 or TDi-1
+	fn presence(&self) -> u8 {
+		[self.ta, self.tb, self.tc, self.td].iter().enumerate()
+			.filter(|(_, byte)| byte.is_some())
+			.fold(0, |y, (i, _)| y | 0x10 << i)
+	}
+}
+
+/// Answer to reset
+#[derive(PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Atr {
+	/// Initial character, 0x3b for direct and 0x3f for inverse convention
+	pub ts: u8,
+	/// Interface bytes, the first element holding TA1, TB1, TC1 and TD1
+	pub interface_bytes: Vec<InterfaceBytes>,
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex_vec"))]
+	pub historical_bytes: Vec<u8>,
+	/// Check byte, present unless only T=0 is indicated
+	pub tck: Option<u8>,
+}
+
+impl Atr {
+	pub fn parse(atr: &[u8]) -> Result<Self, ParseError> {
+		let truncated = |expected: usize| ParseError::Truncated{ expected, len: atr.len() };
+		let ts = *atr.first().ok_or(truncated(1))?;
+		if ts != TS_DIRECT && ts != TS_INVERSE {
+			return Err(ParseError::InvalidValue{ offset: 0, value: ts });
+		}
+		let t0 = *atr.get(1).ok_or(truncated(2))?;
+		let mut interface_bytes = vec![];
+		let mut y = t0 & 0xf0;
+		let mut offset = 2;
+		let mut tck_needed = false;
+		while y != 0 || interface_bytes.is_empty() {
+			let mut group = InterfaceBytes::default();
+			for (bit, byte) in [&mut group.ta, &mut group.tb, &mut group.tc, &mut group.td].into_iter().enumerate() {
+				if y & 0x10 << bit != 0 {
+					*byte = Some(*atr.get(offset).ok_or(truncated(offset + 1))?);
+					offset += 1;
+				}
+			}
+			y = group.td.unwrap_or(0) & 0xf0;
+			tck_needed |= group.td.is_some_and(|td| td & 0x0f != 0);
+			interface_bytes.push(group);
+		}
+		let historical_len = (t0 & 0x0f) as usize;
+		let historical_bytes = atr.get(offset..offset + historical_len).ok_or(truncated(offset + historical_len))?.to_vec();
+		offset += historical_len;
+		let tck = if tck_needed {
+			let tck = *atr.get(offset).ok_or(truncated(offset + 1))?;
+			// All bytes from T0 to TCK must XOR to 0
+			if atr[1..=offset].iter().fold(0, |check, byte| check ^ byte) != 0 {
+				return Err(ParseError::InvalidValue{ offset, value: tck });
+			}
+			offset += 1;
+			Some(tck)
+		} else {
+			None
+		};
+		if let Some(&extra) = atr.get(offset) {
+			return Err(ParseError::InvalidValue{ offset, value: extra });
+		}
+		Ok(Self{ ts, interface_bytes, historical_bytes, tck })
+	}
+
+	pub fn builder() -> AtrBuilder {
+		AtrBuilder::default()
+	}
+
+	/// Returns the ATR, with the presence bits and TCK computed from the fields
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes = vec![self.ts];
+		let y1 = self.interface_bytes.first().map(|group| group.presence()).unwrap_or(0);
+		bytes.push(y1 | (self.historical_bytes.len() as u8 & 0x0f));
+		for group in &self.interface_bytes {
+			bytes.extend([group.ta, group.tb, group.tc, group.td].into_iter().flatten());
+		}
+		bytes.extend_from_slice(&self.historical_bytes);
+		if self.protocols().iter().any(|protocol| *protocol != 0) {
+			bytes.push(bytes[1..].iter().fold(0, |check, byte| check ^ byte));
+		}
+		bytes
+	}
+
+	pub fn inverse_convention(&self) -> bool {
+		self.ts == TS_INVERSE
+	}
+
+	fn interface_byte(&self, i: usize, byte: fn(&InterfaceBytes) -> Option<u8>) -> Option<u8> {
+		self.interface_bytes.get(i.checked_sub(1)?).and_then(byte)
+	}
+
+	/// Returns TAi, counting from 1
+	pub fn ta(&self, i: usize) -> Option<u8> {
+		self.interface_byte(i, |group| group.ta)
+	}
+
+	/// Returns TBi, counting from 1
+	pub fn tb(&self, i: usize) -> Option<u8> {
+		self.interface_byte(i, |group| group.tb)
+	}
+
+	/// Returns TCi, counting from 1
+	pub fn tc(&self, i: usize) -> Option<u8> {
+		self.interface_byte(i, |group| group.tc)
+	}
+
+	/// Returns TDi, counting from 1
+	pub fn td(&self, i: usize) -> Option<u8> {
+		self.interface_byte(i, |group| group.td)
+	}
+
+	/// Returns the protocols indicated in TDi (such as 0 for T=0 and 1 for T=1), T=0 if none are
+	pub fn protocols(&self) -> Vec<u8> {
+		let mut protocols: Vec<u8> = self.interface_bytes.iter().filter_map(|group| group.td).map(|td| td & 0x0f).collect();
+		protocols.dedup();
+		if protocols.is_empty() {
+			protocols.push(0);
+		}
+		protocols
+	}
+
+	/// Returns the clock rate conversion integer from TA1, or `None` if its code is RFU
+	pub fn fi(&self) -> Option<u16> {
+		FI[(self.ta(1).unwrap_or(0x11) >> 4) as usize]
+	}
+
+	/// Returns the baud rate adjustment integer from TA1, or `None` if its code is RFU
+	pub fn di(&self) -> Option<u8> {
+		DI[(self.ta(1).unwrap_or(0x11) & 0x0f) as usize]
+	}
+
+	/// Returns the extra guard time integer from TC1
+	pub fn extra_guard_time(&self) -> u8 {
+		self.tc(1).unwrap_or(0)
+	}
+
+	/// Returns the protocol of the specific mode indicated by TA2, or `None` for negotiable mode
+	pub fn specific_mode(&self) -> Option<u8> {
+		self.ta(2).map(|ta| ta & 0x0f)
+	}
+
+	// The first interface bytes after TDi-1 indicating T=1, from i = 3
+	fn t1_interface_bytes(&self) -> Option<&InterfaceBytes> {
+		self.interface_bytes.windows(2).skip(1)
+			.find(|groups| groups[0].td.is_some_and(|td| td & 0x0f == 1))
+			.map(|groups| &groups[1])
+	}
+
+	/// Returns the information field size of the card for T=1 (IFSC), in bytes
+	pub fn ifsc(&self) -> u8 {
+		self.t1_interface_bytes().and_then(|group| group.ta).unwrap_or(32)
+	}
+
+	/// Returns the block waiting time integer for T=1
+	pub fn bwi(&self) -> u8 {
+		self.t1_interface_bytes().and_then(|group| group.tb).map(|tb| tb >> 4).unwrap_or(4)
+	}
+
+	/// Returns the character waiting time integer for T=1
+	pub fn cwi(&self) -> u8 {
+		self.t1_interface_bytes().and_then(|group| group.tb).map(|tb| tb & 0x0f).unwrap_or(13)
+	}
+
+	/// Decodes the historical bytes
+	pub fn historical(&self) -> Result<HistoricalBytes, ParseError> {
+		HistoricalBytes::parse(&self.historical_bytes)
+	}
+
+	/// Returns the pseudo-ATR of a PC/SC reader for a contactless card with these historical bytes
+	pub fn pcsc_contactless(historical_bytes: &[u8]) -> Self {
+		// Only 15 historical bytes fit in an ATR, PC/SC readers drop the rest
+		let historical_bytes = &historical_bytes[..historical_bytes.len().min(15)];
+		Self::builder().protocol(0).protocol(1).historical_bytes(historical_bytes).build()
+			.expect("PC/SC pseudo-ATR fits in an ATR")
+	}
+
+	/// Returns the pseudo-ATR of a PC/SC reader for a storage card, with its standard byte (SS) and card name (NN)
+	pub fn pcsc_storage_card(standard: u8, card_name: u16) -> Self {
+		let mut historical_bytes = vec![0x80, 0x4f, 0x0c];
+		historical_bytes.extend_from_slice(&PCSC_RID);
+		historical_bytes.push(standard);
+		historical_bytes.extend_from_slice(&card_name.to_be_bytes());
+		historical_bytes.extend_from_slice(&[0x00; 4]);
+		Self::pcsc_contactless(&historical_bytes)
+	}
+}
+
+impl fmt::Debug for Atr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Atr")
+			.field("ts", &self.ts)
+			.field("interface_bytes", &self.interface_bytes)
+			.field("historical_bytes", &Redacted(&self.historical_bytes))
+			.field("tck", &self.tck)
+			.finish()
+	}
+}
+
+impl Iso14443bi {
+	/// Parses `atr`, or returns `None` if the target did not send one
+	pub fn decode_atr(&self) -> Result<Option<Atr>, ParseError> {
+		if self.atr_len == 0 {
+			return Ok(None);
+		}
+		Atr::parse(self.atr()).map(Some)
+	}
+}
+
+// Standard bytes (SS) of PC/SC part 3
+const PCSC_ISO14443A_3: u8 = 0x03;
+const PCSC_ISO14443B_3: u8 = 0x07;
+const PCSC_FELICA: u8 = 0x11;
+
+impl TargetInfo {
+	/// Returns the pseudo-ATR a PC/SC reader would report for this target, or
+	/// `None` for targets without one (D.E.P., barcodes, B' targets that sent
+	/// no ATR and ISO/IEC 14443-4 targets whose ATS does not parse).
+	///
+	/// ISO/IEC 14443-4 targets get their ATS historical bytes, or the application
+	/// data and protocol info of their ATQB. Storage cards get the PC/SC card name
+	/// known from their SAK, or 0000 if there is none.
+	pub fn pcsc_atr(&self) -> Option<Atr> {
+		match self {
+			TargetInfo::Iso14443a(info) if !info.ats().is_empty() => {
+				Ats::parse(info.ats()).ok().map(|ats| Atr::pcsc_contactless(&ats.historical_bytes))
+			},
+			TargetInfo::Iso14443a(info) => {
+				let card_name = match info.sak {
+					0x00 => 0x0003,
+					0x08 => 0x0001,
+					0x09 => 0x0026,
+					0x10 => 0x0038,
+					0x11 => 0x0039,
+					0x18 => 0x0002,
+					_ => 0x0000,
+				};
+				Some(Atr::pcsc_storage_card(PCSC_ISO14443A_3, card_name))
+			},
+			TargetInfo::Iso14443b(info) => {
+				// The last byte holds the MBLI of the ATTRIB response, which libnfc does not report
+				let mut historical_bytes = info.application_data.to_vec();
+				historical_bytes.extend_from_slice(&info.protocol_info);
+				historical_bytes.push(0x00);
+				Some(Atr::pcsc_contactless(&historical_bytes))
+			},
+			TargetInfo::Iso14443bi(info) => info.decode_atr().ok().flatten(),
+			TargetInfo::Felica(_) => Some(Atr::pcsc_storage_card(PCSC_FELICA, 0x003b)),
+			TargetInfo::Jewel(_) => Some(Atr::pcsc_storage_card(PCSC_ISO14443A_3, 0x002f)),
+			TargetInfo::Iso14443b2sr(_) | TargetInfo::Iso14443b2ct(_) | TargetInfo::Iso14443biClass(_) => {
+				Some(Atr::pcsc_storage_card(PCSC_ISO14443B_3, 0x0000))
+			},
+			TargetInfo::Dep(_) | TargetInfo::Barcode(_) => None,
+		}
+	}
+}
+
+impl Target {
+	/// See `TargetInfo::pcsc_atr`
+	pub fn pcsc_atr(&self) -> Option<Atr> {
+		self.target_info.pcsc_atr()
+	}
+}
+
+/// Builder for `Atr`.
+///
+/// Interface bytes are given by their group, counting from 1 like TA1. Each
+/// protocol added sets the next TDi, so bytes of group i need i - 1 protocols.
+/// A TCK is added unless only T=0 is indicated.
+#[derive(Clone, Default)]
+pub struct AtrBuilder {
+	inverse_convention: bool,
+	groups: Vec<InterfaceBytes>,
+	protocols: Vec<u8>,
+	historical_bytes: Vec<u8>,
+}
+
+impl AtrBuilder {
+	fn group(&mut self, i: usize) -> &mut InterfaceBytes {
+		if self.groups.len() < i {
+			self.groups.resize(i, InterfaceBytes::default());
+		}
+		&mut self.groups[i - 1]
+	}
+
+	pub fn inverse_convention(mut self, inverse: bool) -> Self {
+		self.inverse_convention = inverse;
+		self
+	}
+
+	/// Sets TAi; `i` starts from 1
+	pub fn ta(mut self, i: usize, value: u8) -> Self {
+		self.group(i.max(1)).ta = Some(value);
+		self
+	}
+
+	/// Sets TBi; `i` starts from 1
+	pub fn tb(mut self, i: usize, value: u8) -> Self {
+		self.group(i.max(1)).tb = Some(value);
+		self
+	}
+
+	/// Sets TCi; `i` starts from 1
+	pub fn tc(mut self, i: usize, value: u8) -> Self {
+		self.group(i.max(1)).tc = Some(value);
+		self
+	}
+
+	/// Indicates a protocol in the next TDi
+	pub fn protocol(mut self, protocol: u8) -> Self {
+		self.protocols.push(protocol);
+		self
+	}
+
+	pub fn historical_bytes(mut self, historical_bytes: &[u8]) -> Self {
+		self.historical_bytes = historical_bytes.to_vec();
+		self
+	}
+
+	pub fn build(self) -> Result<Atr, BuildError> {
+		if self.groups.len() > self.protocols.len() + 1 {
+			return Err(BuildError::Missing("protocol"));
+		}
+		if let Some(&protocol) = self.protocols.iter().find(|protocol| **protocol > 0x0f) {
+			return Err(BuildError::OutOfRange{ field: "protocol", value: protocol, max: 0x0f });
+		}
+		if self.historical_bytes.len() > 15 {
+			return Err(BuildError::TooLong{ field: "historical bytes", len: self.historical_bytes.len(), max: 15 });
+		}
+		let mut groups = self.groups;
+		groups.resize(self.protocols.len() + 1, InterfaceBytes::default());
+		// TDi indicates the protocol and whether more interface bytes follow
+		for i in (0..self.protocols.len()).rev() {
+			let y = groups[i + 1].presence();
+			groups[i].td = Some(y | self.protocols[i]);
+		}
+		if groups.len() > 1 && groups.last() == Some(&InterfaceBytes::default()) {
+			groups.pop();
+			if let Some(td) = &mut groups.last_mut().unwrap().td {
+				*td &= 0x0f;
+			}
+		}
+		let mut atr = Atr{
+			ts: if self.inverse_convention { TS_INVERSE } else { TS_DIRECT },
+			interface_bytes: groups,
+			historical_bytes: self.historical_bytes,
+			tck: None,
+		};
+		let bytes = atr.to_bytes();
+		if bytes.len() > MAX_LEN {
+			return Err(BuildError::TooLong{ field: "atr", len: bytes.len(), max: MAX_LEN });
+		}
+		if atr.protocols().iter().any(|protocol| *protocol != 0) {
+			atr.tck = bytes.last().copied();
+		}
+		Ok(atr)
+	}
+}
@@ -0,0 +1,276 @@
+//! Answer to select (ATS) of ISO/IEC 14443-4 targets.
+
+use crate::redact::Redacted;
+use crate::target_info::BuildError;
+use crate::{BaudRate, ParseError};
+use std::fmt;
+use std::time::Duration;
+
+/// Frame sizes by FSCI, from ISO/IEC 14443-4:2016. Values above 0xC are RFU and
+/// must be treated as 0xC.
+pub(crate) const FRAME_SIZES: [u16; 13] = [16, 24, 32, 40, 48, 64, 96, 128, 256, 512, 1024, 2048, 4096];
+
+/// Carrier frequency (fc)
+const CARRIER_HZ: u64 = 13_560_000;
+
+const T0_TA: u8 = 0x10;
+const T0_TB: u8 = 0x20;
+const T0_TC: u8 = 0x40;
+
+// Values which apply when an interface byte is absent
+const DEFAULT_TA: u8 = 0x00;
+const DEFAULT_TB: u8 = 0x40;
+const DEFAULT_TC: u8 = 0x02;
+
+/// Answer to select, as found in `Iso14443a::ats` (without the length byte TL)
+#[derive(PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ats {
+	/// Frame size for proximity card integer, the low nibble of the format byte T0
+	pub fsci: u8,
+	/// Interface byte TA(1), with the supported bit rates
+	pub ta: Option<u8>,
+	/// Interface byte TB(1), with the FWI and SFGI
+	pub tb: Option<u8>,
+	/// Interface byte TC(1), with the supported protocol options
+	pub tc: Option<u8>,
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex_vec"))]
+	pub historical_bytes: Vec<u8>,
+}
+
+// The bit rates of a direction supported in TA(1) or the ATQB, from its lowest bit
+pub(crate) fn bit_rates(ta: u8) -> Vec<BaudRate> {
+	let mut bit_rates = vec![BaudRate::Baud106];
+	for (bit, baud_rate) in [BaudRate::Baud212, BaudRate::Baud424, BaudRate::Baud847].into_iter().enumerate() {
+		if ta & 1 << bit != 0 {
+			bit_rates.push(baud_rate);
+		}
+	}
+	bit_rates
+}
+
+fn bit_rate_bits(bit_rates: &[BaudRate]) -> u8 {
+	bit_rates.iter().fold(0, |bits, baud_rate| bits | match baud_rate {
+		BaudRate::Baud212 => 0x01,
+		BaudRate::Baud424 => 0x02,
+		BaudRate::Baud847 => 0x04,
+		_ => 0x00,
+	})
+}
+
+// 256 * 16 / fc * 2^integer
+pub(crate) fn waiting_time(integer: u8) -> Duration {
+	Duration::from_nanos(256 * 16 * (1u64 << integer) * 1_000_000_000 / CARRIER_HZ)
+}
+
+impl Ats {
+	/// Parses an ATS without its length byte, like libnfc stores it
+	pub fn parse(ats: &[u8]) -> Result<Self, ParseError> {
+		let Some(&t0) = ats.first() else { return Err(ParseError::Truncated{ expected: 1, len: 0 }) };
+		if t0 & 0x80 != 0 {
+			return Err(ParseError::InvalidValue{ offset: 0, value: t0 });
+		}
+		let expected = 1 + (t0 & (T0_TA | T0_TB | T0_TC)).count_ones() as usize;
+		if ats.len() < expected {
+			return Err(ParseError::Truncated{ expected, len: ats.len() });
+		}
+		let mut offset = 1;
+		let mut interface_byte = |present: u8| {
+			if t0 & present == 0 {
+				return None;
+			}
+			offset += 1;
+			Some(ats[offset - 1])
+		};
+		let (ta, tb, tc) = (interface_byte(T0_TA), interface_byte(T0_TB), interface_byte(T0_TC));
+		Ok(Self{ fsci: t0 & 0x0f, ta, tb, tc, historical_bytes: ats[expected..].to_vec() })
+	}
+
+	/// Parses an ATS starting with its length byte TL, as received in response to RATS
+	pub fn parse_with_length(ats: &[u8]) -> Result<Self, ParseError> {
+		let Some(&tl) = ats.first() else { return Err(ParseError::Truncated{ expected: 1, len: 0 }) };
+		if (tl as usize) < ats.len() {
+			return Err(ParseError::InvalidValue{ offset: 0, value: tl });
+		}
+		if (tl as usize) > ats.len() {
+			return Err(ParseError::Truncated{ expected: tl as usize, len: ats.len() });
+		}
+		Self::parse(&ats[1..])
+	}
+
+	pub fn builder() -> AtsBuilder {
+		AtsBuilder::default()
+	}
+
+	/// Returns the ATS without its length byte, as used by `Iso14443aBuilder::ats`
+	pub fn to_bytes(&self) -> Vec<u8> {
+		let mut t0 = self.fsci & 0x0f;
+		let mut bytes = vec![0];
+		for (present, byte) in [(T0_TA, self.ta), (T0_TB, self.tb), (T0_TC, self.tc)] {
+			if let Some(byte) = byte {
+				t0 |= present;
+				bytes.push(byte);
+			}
+		}
+		bytes[0] = t0;
+		bytes.extend_from_slice(&self.historical_bytes);
+		bytes
+	}
+
+	/// Returns the maximum frame size the card accepts (FSC), in bytes
+	pub fn fsc(&self) -> u16 {
+		FRAME_SIZES[(self.fsci as usize).min(FRAME_SIZES.len() - 1)]
+	}
+
+	/// Returns whether the same bit rate must be used in both directions
+	pub fn same_bit_rate_required(&self) -> bool {
+		self.ta.unwrap_or(DEFAULT_TA) & 0x80 != 0
+	}
+
+	/// Returns the bit rates supported from card to reader (DS)
+	pub fn picc_to_pcd_bit_rates(&self) -> Vec<BaudRate> {
+		bit_rates(self.ta.unwrap_or(DEFAULT_TA) >> 4)
+	}
+
+	/// Returns the bit rates supported from reader to card (DR)
+	pub fn pcd_to_picc_bit_rates(&self) -> Vec<BaudRate> {
+		bit_rates(self.ta.unwrap_or(DEFAULT_TA))
+	}
+
+	/// Returns the frame waiting time integer, with the RFU value 15 treated as the default 4
+	pub fn fwi(&self) -> u8 {
+		match self.tb.unwrap_or(DEFAULT_TB) >> 4 {
+			15 => DEFAULT_TB >> 4,
+			fwi => fwi,
+		}
+	}
+
+	/// Returns the start-up frame guard time integer, with the RFU value 15 treated as 0
+	pub fn sfgi(&self) -> u8 {
+		match self.tb.unwrap_or(DEFAULT_TB) & 0x0f {
+			15 => 0,
+			sfgi => sfgi,
+		}
+	}
+
+	/// Returns the frame waiting time (FWT)
+	pub fn fwt(&self) -> Duration {
+		waiting_time(self.fwi())
+	}
+
+	/// Returns the start-up frame guard time (SFGT), or `None` if no guard time is required
+	pub fn sfgt(&self) -> Option<Duration> {
+		match self.sfgi() {
+			0 => None,
+			sfgi => Some(waiting_time(sfgi)),
+		}
+	}
+
+	pub fn nad_supported(&self) -> bool {
+		self.tc.unwrap_or(DEFAULT_TC) & 0x01 != 0
+	}
+
+	pub fn cid_supported(&self) -> bool {
+		self.tc.unwrap_or(DEFAULT_TC) & 0x02 != 0
+	}
+}
+
+impl fmt::Debug for Ats {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Ats")
+			.field("fsci", &self.fsci)
+			.field("ta", &self.ta)
+			.field("tb", &self.tb)
+			.field("tc", &self.tc)
+			.field("historical_bytes", &Redacted(&self.historical_bytes))
+			.finish()
+	}
+}
+
+/// Builder for `Ats`, for emulating ISO/IEC 14443-4 targets.
+///
+/// Interface bytes are only included once one of their fields is set, starting
+/// from the values which apply when they are absent. The FSCI defaults to 8
+/// (256 bytes).
+#[derive(Clone)]
+pub struct AtsBuilder {
+	fsci: u8,
+	ta: Option<u8>,
+	fwi: Option<u8>,
+	sfgi: Option<u8>,
+	tc: Option<u8>,
+	historical_bytes: Vec<u8>,
+}
+
+impl Default for AtsBuilder {
+	fn default() -> Self {
+		Self{ fsci: 8, ta: None, fwi: None, sfgi: None, tc: None, historical_bytes: vec![] }
+	}
+}
+
+impl AtsBuilder {
+	pub fn fsci(mut self, fsci: u8) -> Self {
+		self.fsci = fsci;
+		self
+	}
+
+	/// Sets the supported bit rates of both directions in TA(1); 106 kbps is always supported
+	pub fn bit_rates(mut self, picc_to_pcd: &[BaudRate], pcd_to_picc: &[BaudRate]) -> Self {
+		let ta = self.ta.unwrap_or(DEFAULT_TA) & 0x88;
+		self.ta = Some(ta | bit_rate_bits(picc_to_pcd) << 4 | bit_rate_bits(pcd_to_picc));
+		self
+	}
+
+	pub fn same_bit_rate_required(mut self, required: bool) -> Self {
+		let ta = self.ta.unwrap_or(DEFAULT_TA) & !0x80;
+		self.ta = Some(ta | if required { 0x80 } else { 0x00 });
+		self
+	}
+
+	pub fn fwi(mut self, fwi: u8) -> Self {
+		self.fwi = Some(fwi);
+		self
+	}
+
+	pub fn sfgi(mut self, sfgi: u8) -> Self {
+		self.sfgi = Some(sfgi);
+		self
+	}
+
+	pub fn nad_supported(mut self, supported: bool) -> Self {
+		self.tc = Some(self.tc.unwrap_or(DEFAULT_TC) & !0x01 | supported as u8);
+		self
+	}
+
+	pub fn cid_supported(mut self, supported: bool) -> Self {
+		self.tc = Some(self.tc.unwrap_or(DEFAULT_TC) & !0x02 | (supported as u8) << 1);
+		self
+	}
+
+	pub fn historical_bytes(mut self, historical_bytes: &[u8]) -> Self {
+		self.historical_bytes = historical_bytes.to_vec();
+		self
+	}
+
+	pub fn build(self) -> Result<Ats, BuildError> {
+		if self.fsci > 0x0c {
+			return Err(BuildError::OutOfRange{ field: "FSCI", value: self.fsci, max: 0x0c });
+		}
+		let fwi = self.fwi.unwrap_or(DEFAULT_TB >> 4);
+		if fwi > 14 {
+			return Err(BuildError::OutOfRange{ field: "FWI", value: fwi, max: 14 });
+		}
+		let sfgi = self.sfgi.unwrap_or(DEFAULT_TB & 0x0f);
+		if sfgi > 14 {
+			return Err(BuildError::OutOfRange{ field: "SFGI", value: sfgi, max: 14 });
+		}
+		let tb = (self.fwi.is_some() || self.sfgi.is_some()).then_some(fwi << 4 | sfgi);
+		let ats = Ats{ fsci: self.fsci, ta: self.ta, tb, tc: self.tc, historical_bytes: self.historical_bytes };
+		// Same limit as the ats array of Iso14443a
+		let len = ats.to_bytes().len();
+		if len > 254 {
+			return Err(BuildError::TooLong{ field: "ats", len, max: 254 });
+		}
+		Ok(ats)
+	}
+}
@@ -0,0 +1,43 @@
+use crate::{Mode, Modulation, ModulationType};
+
+/// What a device can do, as reported by `Device::capabilities`.
+///
+/// Every supported combination of modulation type and baud rate is listed per
+/// mode. This type does not need `libnfc`, so capabilities reported elsewhere
+/// can be checked against the needs of a deployment.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Capabilities {
+	pub name: String,
+	pub connstring: String,
+	/// Driver name, the first part of the connstring (such as `pn53x_usb`)
+	pub driver: String,
+	/// Modulations supported in initiator (reader) mode
+	pub initiator: Vec<Modulation>,
+	/// Modulations supported in target (emulation) mode
+	pub target: Vec<Modulation>,
+}
+
+impl Capabilities {
+	pub fn modulations(&self, mode: Mode) -> &[Modulation] {
+		match mode {
+			Mode::Initiator => &self.initiator,
+			Mode::Target => &self.target,
+		}
+	}
+
+	/// Returns whether the modulation type is supported at the baud rate
+	pub fn supports(&self, mode: Mode, modulation: &Modulation) -> bool {
+		self.modulations(mode).contains(modulation)
+	}
+
+	/// Returns whether the modulation type is supported at any baud rate
+	pub fn supports_modulation_type(&self, mode: Mode, modulation_type: ModulationType) -> bool {
+		self.modulations(mode).iter().any(|modulation| modulation.modulation_type == modulation_type)
+	}
+
+	/// Returns the modulations of `required` which are not supported
+	pub fn missing(&self, mode: Mode, required: &[Modulation]) -> Vec<Modulation> {
+		required.iter().filter(|modulation| !self.supports(mode, modulation)).copied().collect()
+	}
+}
@@ -0,0 +1,397 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Maximum number of user-defined devices a libnfc context can hold (MAX_USER_DEFINED_DEVICES)
+pub const MAX_USER_DEFINED_DEVICES: usize = 4;
+/// Size of the device name buffer in libnfc, including the NUL terminator (DEVICE_NAME_LENGTH)
+pub const DEVICE_NAME_LENGTH: usize = 256;
+/// Size of the connstring buffer in libnfc, including the NUL terminator (NFC_BUFSIZE_CONNSTRING)
+pub const CONNSTRING_LENGTH: usize = 1024;
+
+/// Global libnfc options and user-defined devices, as found in `libnfc.conf` and `devices.d/*.conf`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Config {
+	/// Allow device auto-detection (default: true)
+	pub allow_autoscan: bool,
+	/// Allow intrusive auto-detection (default: false)
+	pub allow_intrusive_scan: bool,
+	/// Global and per-group log level bitmask (default: 1, error)
+	pub log_level: u32,
+	/// User-defined devices, at most `MAX_USER_DEFINED_DEVICES`
+	pub devices: Vec<DeviceConfig>,
+}
+
+/// A user-defined device, either from `device.*` keys in `libnfc.conf` or from a `devices.d/*.conf` file
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct DeviceConfig {
+	/// Name reported by the device, shorter than `DEVICE_NAME_LENGTH`
+	pub name: String,
+	/// Connection string used to open the device, such as `pn532_uart:/dev/ttyUSB0`, shorter than `CONNSTRING_LENGTH`
+	pub connstring: String,
+	/// Whether libnfc ignores the device when it cannot be opened, instead of logging an error
+	pub optional: bool,
+}
+
+/// Error kinds produced while parsing or validating a libnfc configuration
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ConfigErrorKind {
+	Io(ErrorKind),
+	Syntax,
+	UnknownKey(String),
+	InvalidBoolean(String),
+	InvalidLogLevel(String),
+	TooManyDevices,
+	MissingName(usize),
+	MissingConnstring(usize),
+	NameTooLong(usize),
+	ConnstringTooLong(usize),
+	UnrepresentableValue(String),
+}
+
+/// Error produced while parsing or validating a libnfc configuration
+#[derive(Debug, Clone)]
+pub struct ConfigError {
+	pub kind: ConfigErrorKind,
+	/// File the error was found in, if loaded from disk
+	pub path: Option<PathBuf>,
+	/// 1-based line number the error was found on, if any
+	pub line: Option<usize>,
+	// The I/O error of `ConfigErrorKind::Io`, returned by `source`
+	io_error: Option<Arc<io::Error>>,
+}
+
+impl ConfigError {
+	fn new(kind: ConfigErrorKind) -> Self {
+		Self{ kind, path: None, line: None, io_error: None }
+	}
+
+	fn at_line(kind: ConfigErrorKind, line: usize) -> Self {
+		Self{ kind, path: None, line: Some(line), io_error: None }
+	}
+
+	fn io(err: io::Error) -> Self {
+		Self{ kind: ConfigErrorKind::Io(err.kind()), path: None, line: None, io_error: Some(Arc::new(err)) }
+	}
+
+	fn in_file(mut self, path: &Path) -> Self {
+		self.path = Some(path.to_path_buf());
+		self
+	}
+}
+
+impl fmt::Display for ConfigErrorKind {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ConfigErrorKind::Io(kind) => write!(f, "I/O error: {}", std::io::Error::from(*kind)),
+			ConfigErrorKind::Syntax => write!(f, "Syntax error, expected `key = value`"),
+			ConfigErrorKind::UnknownKey(key) => write!(f, "Unknown key: {}", key),
+			ConfigErrorKind::InvalidBoolean(value) => write!(f, "Invalid boolean value: {}", value),
+			ConfigErrorKind::InvalidLogLevel(value) => write!(f, "Invalid log level: {}", value),
+			ConfigErrorKind::TooManyDevices => write!(f, "Configuration exceeded maximum user-defined devices ({})", MAX_USER_DEFINED_DEVICES),
+			ConfigErrorKind::MissingName(index) => write!(f, "Device #{} has no name", index),
+			ConfigErrorKind::MissingConnstring(index) => write!(f, "Device #{} has no connstring", index),
+			ConfigErrorKind::NameTooLong(index) => write!(f, "Device #{} name is longer than {} bytes", index, DEVICE_NAME_LENGTH - 1),
+			ConfigErrorKind::ConnstringTooLong(index) => write!(f, "Device #{} connstring is longer than {} bytes", index, CONNSTRING_LENGTH - 1),
+			ConfigErrorKind::UnrepresentableValue(value) => write!(f, "Value cannot be written to a libnfc configuration file: {:?}", value),
+		}
+	}
+}
+
+// The I/O error itself is not compared, its kind is
+impl PartialEq for ConfigError {
+	fn eq(&self, other: &Self) -> bool {
+		self.kind == other.kind && self.path == other.path && self.line == other.line
+	}
+}
+
+impl Eq for ConfigError {}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let kind = match &self.io_error {
+			Some(err) => format!("I/O error: {}", err),
+			None => self.kind.to_string(),
+		};
+		match (&self.path, self.line) {
+			(Some(path), Some(line)) => write!(f, "{}:{}: {}", path.display(), line, kind),
+			(Some(path), None) => write!(f, "{}: {}", path.display(), kind),
+			(None, Some(line)) => write!(f, "line {}: {}", line, kind),
+			(None, None) => write!(f, "{}", kind),
+		}
+	}
+}
+
+impl std::error::Error for ConfigError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		self.io_error.as_deref().map(|err| err as &(dyn std::error::Error + 'static))
+	}
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self{
+			allow_autoscan: true,
+			allow_intrusive_scan: false,
+			log_level: 1,
+			devices: Vec::new(),
+		}
+	}
+}
+
+// Mirrors parse_line() in libnfc/conf.c
+fn parse_line(line: &str) -> Result<Option<(&str, &str)>, ConfigErrorKind> {
+	let line = line.trim_start();
+	if line.is_empty() || line.starts_with('#') {
+		return Ok(None);
+	}
+
+	let key_len = line.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.')).unwrap_or(line.len());
+	if key_len == 0 {
+		return Err(ConfigErrorKind::Syntax);
+	}
+	let (key, rest) = line.split_at(key_len);
+	let rest = rest.trim_start().strip_prefix('=').ok_or(ConfigErrorKind::Syntax)?.trim_start();
+	if rest.is_empty() {
+		return Err(ConfigErrorKind::Syntax);
+	}
+
+	// Escaped value: "..."
+	if let Some(quoted) = rest.strip_prefix('"') {
+		if let Some(end) = quoted.find('"') {
+			if quoted[end + 1..].trim().is_empty() {
+				return Ok(Some((key, &quoted[..end])));
+			}
+		}
+	}
+
+	// Non-escaped value: everything up to the first whitespace
+	let value_len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+	if !rest[value_len..].trim().is_empty() {
+		return Err(ConfigErrorKind::Syntax);
+	}
+	Ok(Some((key, &rest[..value_len])))
+}
+
+// Mirrors string_as_boolean() in libnfc/nfc-internal.c
+fn parse_bool(value: &str) -> Result<bool, ConfigErrorKind> {
+	match value {
+		"yes" | "true" | "1" => Ok(true),
+		"no" | "false" | "0" => Ok(false),
+		_ => Err(ConfigErrorKind::InvalidBoolean(value.to_string())),
+	}
+}
+
+// device.optional is compared case-sensitively against its own set of values in libnfc
+fn parse_optional(value: &str) -> Result<bool, ConfigErrorKind> {
+	match value {
+		"true" | "True" | "1" => Ok(true),
+		"false" | "False" | "0" => Ok(false),
+		_ => Err(ConfigErrorKind::InvalidBoolean(value.to_string())),
+	}
+}
+
+fn format_value(value: &str) -> Result<String, ConfigErrorKind> {
+	if value.is_empty() || value.contains(['"', '\n', '\r']) {
+		return Err(ConfigErrorKind::UnrepresentableValue(value.to_string()));
+	}
+	Ok(format!("\"{}\"", value))
+}
+
+impl Config {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Parses the contents of a `libnfc.conf` file
+	pub fn parse(input: &str) -> Result<Self, ConfigError> {
+		let mut config = Self::default();
+		config.apply_str(input, "")?;
+		Ok(config)
+	}
+
+	/// Loads `libnfc.conf` and `devices.d/*.conf` from a libnfc configuration directory (i.e. `/etc/nfc`).
+	///
+	/// Missing files are ignored, like libnfc does. Unlike libnfc, device files are read in
+	/// lexicographic order so the resulting device list is deterministic.
+	pub fn load<P: AsRef<Path>>(dir: P) -> Result<Self, ConfigError> {
+		let dir = dir.as_ref();
+		let mut config = Self::default();
+
+		let conf_path = dir.join("libnfc.conf");
+		match fs::read_to_string(&conf_path) {
+			Ok(input) => config.apply_str(&input, "").map_err(|err| err.in_file(&conf_path))?,
+			Err(err) if err.kind() == ErrorKind::NotFound => {},
+			Err(err) => return Err(ConfigError::io(err).in_file(&conf_path)),
+		}
+
+		let devices_dir = dir.join("devices.d");
+		let entries = match fs::read_dir(&devices_dir) {
+			Ok(entries) => entries,
+			Err(err) if err.kind() == ErrorKind::NotFound => return Ok(config),
+			Err(err) => return Err(ConfigError::io(err).in_file(&devices_dir)),
+		};
+		let mut device_paths = vec![];
+		for entry in entries {
+			let entry = entry.map_err(|err| ConfigError::io(err).in_file(&devices_dir))?;
+			let file_name = entry.file_name();
+			let file_name = file_name.to_string_lossy();
+			if !file_name.starts_with('.') && file_name.len() > ".conf".len() && file_name.ends_with(".conf") && entry.path().is_file() {
+				device_paths.push(entry.path());
+			}
+		}
+		device_paths.sort();
+		for device_path in device_paths {
+			let input = fs::read_to_string(&device_path).map_err(|err| ConfigError::io(err).in_file(&device_path))?;
+			config.apply_str(&input, "device.").map_err(|err| err.in_file(&device_path))?;
+		}
+		Ok(config)
+	}
+
+	/// Parses the contents of a `devices.d/*.conf` file and appends the device(s) it defines
+	pub fn add_device_file(&mut self, input: &str) -> Result<(), ConfigError> {
+		self.apply_str(input, "device.")
+	}
+
+	fn apply_str(&mut self, input: &str, key_prefix: &str) -> Result<(), ConfigError> {
+		for (index, line) in input.lines().enumerate() {
+			let lineno = index + 1;
+			if let Some((key, value)) = parse_line(line).map_err(|kind| ConfigError::at_line(kind, lineno))? {
+				let key = format!("{}{}", key_prefix, key);
+				self.apply_key_value(&key, value).map_err(|kind| ConfigError::at_line(kind, lineno))?;
+			}
+		}
+		Ok(())
+	}
+
+	// Mirrors conf_keyvalue_context() in libnfc/conf.c
+	fn apply_key_value(&mut self, key: &str, value: &str) -> Result<(), ConfigErrorKind> {
+		match key {
+			"allow_autoscan" => self.allow_autoscan = parse_bool(value)?,
+			"allow_intrusive_scan" => self.allow_intrusive_scan = parse_bool(value)?,
+			"log_level" => self.log_level = value.parse().map_err(|_| ConfigErrorKind::InvalidLogLevel(value.to_string()))?,
+			"device.name" => self.current_device(|device| !device.name.is_empty())?.name = value.to_string(),
+			"device.connstring" => self.current_device(|device| !device.connstring.is_empty())?.connstring = value.to_string(),
+			"device.optional" => {
+				let optional = parse_optional(value)?;
+				self.current_device(|device| device.optional)?.optional = optional;
+			},
+			_ => return Err(ConfigErrorKind::UnknownKey(key.to_string())),
+		}
+		Ok(())
+	}
+
+	// libnfc starts a new device whenever the field being set is already set on the last device
+	fn current_device<F: Fn(&DeviceConfig) -> bool>(&mut self, is_set: F) -> Result<&mut DeviceConfig, ConfigErrorKind> {
+		if self.devices.last().is_none_or(is_set) {
+			if self.devices.len() >= MAX_USER_DEFINED_DEVICES {
+				return Err(ConfigErrorKind::TooManyDevices);
+			}
+			self.devices.push(DeviceConfig::default());
+		}
+		Ok(self.devices.last_mut().unwrap())
+	}
+
+	/// Checks that this configuration can be loaded by libnfc without being truncated or ignored
+	pub fn validate(&self) -> Result<(), ConfigError> {
+		if self.devices.len() > MAX_USER_DEFINED_DEVICES {
+			return Err(ConfigError::new(ConfigErrorKind::TooManyDevices));
+		}
+		for (index, device) in self.devices.iter().enumerate() {
+			device.validate_at(index)?;
+		}
+		Ok(())
+	}
+}
+
+impl FromStr for Config {
+	type Err = ConfigError;
+	fn from_str(input: &str) -> Result<Self, ConfigError> {
+		Self::parse(input)
+	}
+}
+
+/// Writes this configuration in `libnfc.conf` format. Call `validate` first to ensure the output can be parsed.
+impl fmt::Display for Config {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		writeln!(f, "allow_autoscan = {}", self.allow_autoscan)?;
+		writeln!(f, "allow_intrusive_scan = {}", self.allow_intrusive_scan)?;
+		writeln!(f, "log_level = {}", self.log_level)?;
+		for device in &self.devices {
+			device.write_keys(f, "device.")?;
+		}
+		Ok(())
+	}
+}
+
+impl DeviceConfig {
+	pub fn new(name: &str, connstring: &str) -> Self {
+		Self{
+			name: name.to_string(),
+			connstring: connstring.to_string(),
+			optional: false,
+		}
+	}
+
+	/// Parses the contents of a `devices.d/*.conf` file defining a single device
+	pub fn parse(input: &str) -> Result<Self, ConfigError> {
+		let mut config = Config::default();
+		config.add_device_file(input)?;
+		if config.devices.len() > 1 {
+			return Err(ConfigError::new(ConfigErrorKind::TooManyDevices));
+		}
+		let device = config.devices.pop().unwrap_or_default();
+		device.validate()?;
+		Ok(device)
+	}
+
+	/// Checks that this device can be loaded by libnfc without being truncated or ignored
+	pub fn validate(&self) -> Result<(), ConfigError> {
+		self.validate_at(0)
+	}
+
+	fn validate_at(&self, index: usize) -> Result<(), ConfigError> {
+		let check = |value: &str, too_long: ConfigErrorKind, max_len: usize| {
+			if value.len() >= max_len {
+				return Err(ConfigError::new(too_long));
+			}
+			format_value(value).map(|_| ()).map_err(ConfigError::new)
+		};
+		if self.name.is_empty() {
+			return Err(ConfigError::new(ConfigErrorKind::MissingName(index)));
+		}
+		if self.connstring.is_empty() {
+			return Err(ConfigError::new(ConfigErrorKind::MissingConnstring(index)));
+		}
+		check(&self.name, ConfigErrorKind::NameTooLong(index), DEVICE_NAME_LENGTH)?;
+		check(&self.connstring, ConfigErrorKind::ConnstringTooLong(index), CONNSTRING_LENGTH)
+	}
+
+	fn write_keys(&self, f: &mut fmt::Formatter, key_prefix: &str) -> fmt::Result {
+		let name = format_value(&self.name).map_err(|_| fmt::Error)?;
+		let connstring = format_value(&self.connstring).map_err(|_| fmt::Error)?;
+		writeln!(f, "{}name = {}", key_prefix, name)?;
+		writeln!(f, "{}connstring = {}", key_prefix, connstring)?;
+		if self.optional {
+			writeln!(f, "{}optional = true", key_prefix)?;
+		}
+		Ok(())
+	}
+}
+
+impl FromStr for DeviceConfig {
+	type Err = ConfigError;
+	fn from_str(input: &str) -> Result<Self, ConfigError> {
+		Self::parse(input)
+	}
+}
+
+/// Writes this device in `devices.d/*.conf` format. Call `validate` first to ensure the output can be parsed.
+impl fmt::Display for DeviceConfig {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.write_keys(f, "")
+	}
+}
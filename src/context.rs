@@ -1,8 +1,13 @@
 use crate::{Error, Result, Device};
-use nfc1_sys::{nfc_connstring, nfc_context, nfc_context_free, nfc_context_new, nfc_init, nfc_list_devices};
+#[cfg(feature = "drivers")]
+use crate::config::{Config, ConfigError};
+use crate::ffi::{nfc_context_free, nfc_context_new, nfc_init, nfc_list_devices};
+use nfc1_sys::{nfc_connstring, nfc_context};
 use std::convert::TryInto;
 use std::ffi::CStr;
 use std::os::raw::c_char;
+#[cfg(feature = "drivers")]
+use std::os::raw::c_uint;
 use std::ptr;
 use std::sync::LazyLock;
 
@@ -32,6 +37,8 @@ unsafe impl Send for Context {}
 
 impl Context {
 	pub fn new() -> Result<Self> {
+		#[cfg(feature = "dlopen")]
+		crate::load_library()?;
 		let ptr = unsafe { nfc_context_new() };
 		if ptr.is_null() {
 			return Err(Error::Malloc);
@@ -40,6 +47,27 @@ impl Context {
 		Ok(Self { ptr, drivers })
 	}
 
+	/// Replaces the options and user-defined devices of this context, as if they were read from `libnfc.conf`.
+	///
+	/// The configuration is validated first, and left unapplied if it is invalid.
+	#[cfg(feature = "drivers")]
+	pub fn apply_config(&mut self, config: &Config) -> std::result::Result<(), ConfigError> {
+		config.validate()?;
+		// XXX: Safe because self.ptr is non-null for the lifetime of self
+		let context = unsafe { &mut *self.ptr };
+		context.allow_autoscan = config.allow_autoscan;
+		context.allow_intrusive_scan = config.allow_intrusive_scan;
+		context.log_level = config.log_level;
+		for (i, user_defined_device) in context.user_defined_devices.iter_mut().enumerate() {
+			let device = config.devices.get(i);
+			copy_c_str(&mut user_defined_device.name, device.map_or("", |device| &device.name));
+			copy_c_str(&mut user_defined_device.connstring, device.map_or("", |device| &device.connstring));
+			user_defined_device.optional = device.is_some_and(|device| device.optional);
+		}
+		context.user_defined_device_count = config.devices.len() as c_uint;
+		Ok(())
+	}
+
 	// NFC Device/Hardware manipulation
 
 	pub fn open(&mut self) -> Result<Device> {
@@ -59,6 +87,15 @@ impl Context {
 	}
 }
 
+#[cfg(feature = "drivers")]
+fn copy_c_str(dst: &mut [c_char], src: &str) {
+	let max_len = dst.len() - 1;
+	dst.fill(0);
+	for (d, s) in dst.iter_mut().zip(src.bytes().take(max_len)) {
+		*d = s as c_char;
+	}
+}
+
 impl Drop for Context {
 	fn drop(&mut self) {
 		unsafe { nfc_context_free(self.ptr); }
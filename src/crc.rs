@@ -0,0 +1,120 @@
+//! ISO/IEC 14443 CRC and parity, without allocation and usable in `const` contexts.
+//!
+//! CRCs are returned in the order they are transmitted (least significant byte
+//! first). Parity is one byte per data byte, holding the parity bit, like the
+//! `_with_parity` methods of `Device` use it.
+
+// Same algorithm as iso14443a_crc and iso14443b_crc in libnfc/iso14443-subr.c
+const fn crc(data: &[u8], init: u16) -> u16 {
+	let mut crc = init;
+	let mut i = 0;
+	while i < data.len() {
+		let mut bt = data[i] ^ (crc & 0xff) as u8;
+		bt ^= bt << 4;
+		let bt = bt as u16;
+		crc = (crc >> 8) ^ (bt << 8) ^ (bt << 3) ^ (bt >> 4);
+		i += 1;
+	}
+	crc
+}
+
+/// Computes the CRC_A of `data`
+pub const fn crc_a(data: &[u8]) -> [u8; 2] {
+	crc(data, 0x6363).to_le_bytes()
+}
+
+/// Computes the CRC_B of `data`
+pub const fn crc_b(data: &[u8]) -> [u8; 2] {
+	(!crc(data, 0xffff)).to_le_bytes()
+}
+
+/// Appends the CRC_A of `data` to it
+pub fn append_crc_a(data: &mut Vec<u8>) {
+	let crc = crc_a(data);
+	data.extend_from_slice(&crc);
+}
+
+/// Appends the CRC_B of `data` to it
+pub fn append_crc_b(data: &mut Vec<u8>) {
+	let crc = crc_b(data);
+	data.extend_from_slice(&crc);
+}
+
+const fn split_crc(frame: &[u8]) -> Option<(&[u8], [u8; 2])> {
+	match frame {
+		[payload @ .., crc0, crc1] => Some((payload, [*crc0, *crc1])),
+		_ => None,
+	}
+}
+
+/// Returns whether `frame` ends with the CRC_A of the bytes before it
+pub const fn check_crc_a(frame: &[u8]) -> bool {
+	match split_crc(frame) {
+		Some((payload, crc)) => {
+			let expected = crc_a(payload);
+			crc[0] == expected[0] && crc[1] == expected[1]
+		},
+		None => false,
+	}
+}
+
+/// Returns whether `frame` ends with the CRC_B of the bytes before it
+pub const fn check_crc_b(frame: &[u8]) -> bool {
+	match split_crc(frame) {
+		Some((payload, crc)) => {
+			let expected = crc_b(payload);
+			crc[0] == expected[0] && crc[1] == expected[1]
+		},
+		None => false,
+	}
+}
+
+/// Returns `frame` without its CRC_A, or `None` if the CRC_A is not valid
+pub const fn strip_crc_a(frame: &[u8]) -> Option<&[u8]> {
+	match split_crc(frame) {
+		Some((payload, _)) if check_crc_a(frame) => Some(payload),
+		_ => None,
+	}
+}
+
+/// Returns `frame` without its CRC_B, or `None` if the CRC_B is not valid
+pub const fn strip_crc_b(frame: &[u8]) -> Option<&[u8]> {
+	match split_crc(frame) {
+		Some((payload, _)) if check_crc_b(frame) => Some(payload),
+		_ => None,
+	}
+}
+
+/// Returns the odd parity bit of `byte`, as used by ISO/IEC 14443A
+pub const fn odd_parity(byte: u8) -> u8 {
+	(byte.count_ones() as u8 & 1) ^ 1
+}
+
+/// Writes the odd parity bit of every byte of `data` to `parity`, which must be at least as long as `data`
+pub fn fill_parity(data: &[u8], parity: &mut [u8]) {
+	for (parity, byte) in parity.iter_mut().zip(data) {
+		*parity = odd_parity(*byte);
+	}
+}
+
+/// Returns the odd parity bit of every byte of `data`
+pub fn parity(data: &[u8]) -> Vec<u8> {
+	data.iter().map(|byte| odd_parity(*byte)).collect()
+}
+
+/// Returns the index of the first byte of `data` whose parity bit is wrong, only considering the lowest bit of `parity`
+pub const fn find_parity_error(data: &[u8], parity: &[u8]) -> Option<usize> {
+	let mut i = 0;
+	while i < data.len() && i < parity.len() {
+		if parity[i] & 1 != odd_parity(data[i]) {
+			return Some(i);
+		}
+		i += 1;
+	}
+	None
+}
+
+/// Returns whether every byte of `data` has a correct parity bit in `parity`
+pub const fn check_parity(data: &[u8], parity: &[u8]) -> bool {
+	data.len() <= parity.len() && find_parity_error(data, parity).is_none()
+}
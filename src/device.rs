@@ -1,7 +1,9 @@
 use crate::{
 	Error,
 	Result,
+	Capabilities,
 	Context,
+	DeviceInformation,
 	Target,
 	target_info::Dep,
 	DepMode,
@@ -14,14 +16,16 @@ use crate::{
 	wrap_err,
 	wrap_err_usize,
 };
+use crate::instrument::Call;
+use crate::trace::TraceSink;
 #[cfg(feature = "driver_pn53x_usb")]
-use nfc1_sys::{
+use crate::ffi::{
 	pn53x_transceive,
 	pn53x_read_register,
 	pn53x_write_register,
 };
-use nfc1_sys::{
-	nfc_device,
+use nfc1_sys::nfc_device;
+use crate::ffi::{
 	nfc_open,
 	nfc_close,
 	nfc_strerror,
@@ -62,9 +66,14 @@ use std::mem::MaybeUninit;
 use std::os::raw::{c_char, c_int, c_void};
 use std::ffi::{CStr, CString};
 use std::ptr;
+use std::sync::{Arc, Mutex};
 
 pub struct Device {
 	ptr: *mut nfc_device,
+	connstring: Arc<str>,
+	modulation: Option<Modulation>,
+	handle_crc: bool,
+	trace_sink: Option<Arc<Mutex<dyn TraceSink>>>,
 }
 
 unsafe impl Send for Device {}
@@ -79,12 +88,35 @@ impl Device {
 			connstring_ptr = connstring_cstring.into_raw();
 		}
 
+		let call = Call::begin("open", &Arc::from(connstring.unwrap_or_default()), None);
 		match unsafe { nfc_open(context.ptr, connstring_ptr).as_mut() } {
-			Some(ptr) => Ok(Self{ ptr }),
-			None => Err(Error::NoDeviceFound)
+			Some(ptr) => {
+				let connstring = unsafe { CStr::from_ptr(nfc_device_get_connstring(ptr)) }.to_string_lossy().into();
+				Ok(Self{ ptr, connstring, modulation: None, handle_crc: true, trace_sink: None })
+			},
+			None => call.check(Err(Error::NoDeviceFound)),
 		}
 	}
 
+	fn call(&self, operation: &'static str) -> Call {
+		self.call_with_modulation(operation, self.modulation.as_ref())
+	}
+
+	fn call_with_modulation(&self, operation: &'static str, modulation: Option<&Modulation>) -> Call {
+		let call = Call::begin(operation, &self.connstring, modulation);
+		match &self.trace_sink {
+			Some(sink) => call.trace(sink.clone(), !self.handle_crc),
+			None => call,
+		}
+	}
+
+	fn selected(&mut self, target: Result<Target>) -> Result<Target> {
+		if let Ok(target) = &target {
+			self.modulation = Some(target.modulation);
+		}
+		target
+	}
+
 	pub fn new(context: &mut Context) -> Result<Self> {
 		Self::new_device(context, None)
 	}
@@ -93,6 +125,17 @@ impl Device {
 		Self::new_device(context, Some(connstring))
 	}
 
+	// Frame tracing
+
+	/// Passes every frame sent or received by this device to `sink`, replacing any previous sink
+	pub fn set_trace_sink(&mut self, sink: impl TraceSink + 'static) {
+		self.trace_sink = Some(Arc::new(Mutex::new(sink)));
+	}
+
+	pub fn clear_trace_sink(&mut self) {
+		self.trace_sink = None;
+	}
+
 	// Error reporting
 
 	pub fn get_last_error_string(&mut self) -> Option<String> {
@@ -115,204 +158,260 @@ impl Device {
 
 	#[cfg(feature = "driver_pn53x_usb")]
 	pub fn pn53x_transceive(&mut self, tx: &[u8], mut rx_len: usize, timeout: Timeout) -> Result<Vec<u8>> {
+		let call = self.call("pn53x_transceive");
+		call.tx(tx);
 		let mut rx_buf = vec![0u8; rx_len];
-		rx_len = wrap_err_usize(unsafe { pn53x_transceive(self.ptr, tx.as_ptr(), tx.len(), rx_buf.as_mut_ptr(), rx_len, timeout.into()) })?;
+		rx_len = call.check(wrap_err_usize(unsafe { pn53x_transceive(self.ptr, tx.as_ptr(), tx.len(), rx_buf.as_mut_ptr(), rx_len, timeout.into()) }))?;
 		rx_buf.resize(rx_len, 0u8);
+		call.rx(&rx_buf);
 		Ok(rx_buf)
 	}
 
 	#[cfg(feature = "driver_pn53x_usb")]
 	pub fn pn53x_read_register(&mut self, register_address: u16) -> Result<u8> {
 		let mut value = 0u8;
-		wrap_err(unsafe { pn53x_read_register(self.ptr, register_address, &mut value) })?;
+		self.call("pn53x_read_register").check(wrap_err(unsafe { pn53x_read_register(self.ptr, register_address, &mut value) }))?;
 		Ok(value)
 	}
 
 	#[cfg(feature = "driver_pn53x_usb")]
 	pub fn pn53x_write_register(&mut self, register_address: u16, symbol_mask: u8, value: u8) -> Result<()> {
-		wrap_err(unsafe { pn53x_write_register(self.ptr, register_address, symbol_mask, value) })
+		self.call("pn53x_write_register").check(wrap_err(unsafe { pn53x_write_register(self.ptr, register_address, symbol_mask, value) }))
 	}
 
 	pub fn abort_command(&mut self) -> Result<()> {
-		wrap_err(unsafe { nfc_abort_command(self.ptr) })
+		self.call("abort_command").check(wrap_err(unsafe { nfc_abort_command(self.ptr) }))
 	}
 
 	pub fn idle(&mut self) -> Result<()> {
-		wrap_err(unsafe { nfc_idle(self.ptr) })
+		self.call("idle").check(wrap_err(unsafe { nfc_idle(self.ptr) }))?;
+		self.modulation = None;
+		Ok(())
 	}
 
 	// NFC initiator: act as "reader"
 
 	pub fn initiator_init(&mut self) -> Result<()> {
-		wrap_err(unsafe { nfc_initiator_init(self.ptr) })
+		self.call("initiator_init").check(wrap_err(unsafe { nfc_initiator_init(self.ptr) }))?;
+		self.modulation = None;
+		self.handle_crc = true;
+		Ok(())
 	}
 
 	pub fn initiator_init_secure_element(&mut self) -> Result<()> {
-		wrap_err(unsafe { nfc_initiator_init_secure_element(self.ptr) })
+		self.call("initiator_init_secure_element").check(wrap_err(unsafe { nfc_initiator_init_secure_element(self.ptr) }))
 	}
 
 	pub fn initiator_select_passive_target_with_init_data(&mut self, modulation: &Modulation, init_data: &[u8]) -> Result<Target> {
+		let call = self.call_with_modulation("initiator_select_passive_target_with_init_data", Some(modulation));
 		let mut target: nfc1_sys::nfc_target = (&Target::new_iso14443a()).into();
-		wrap_err(unsafe { nfc_initiator_select_passive_target(self.ptr, modulation.into(), init_data.as_ptr(), init_data.len(), &mut target) })?;
-		target.try_into()
+		call.check(wrap_err(unsafe { nfc_initiator_select_passive_target(self.ptr, modulation.into(), init_data.as_ptr(), init_data.len(), &mut target) }))?;
+		self.selected(call.check(target.try_into()))
 	}
 
 	pub fn initiator_select_passive_target(&mut self, modulation: &Modulation) -> Result<Target> {
+		let call = self.call_with_modulation("initiator_select_passive_target", Some(modulation));
 		let mut target: nfc1_sys::nfc_target = (&Target::new_iso14443a()).into();
-		wrap_err(unsafe { nfc_initiator_select_passive_target(self.ptr, modulation.into(), ptr::null(), 0, &mut target) })?;
-		target.try_into()
+		call.check(wrap_err(unsafe { nfc_initiator_select_passive_target(self.ptr, modulation.into(), ptr::null(), 0, &mut target) }))?;
+		self.selected(call.check(target.try_into()))
 	}
 
+	/// Returns the targets found, at most `max_len` of them
 	pub fn initiator_list_passive_targets(&mut self, modulation: &Modulation, max_len: usize) -> Result<Vec<Target>> {
+		let call = self.call_with_modulation("initiator_list_passive_targets", Some(modulation));
 		let mut targets: Vec<nfc1_sys::nfc_target> = vec![(&Target::new_iso14443a()).into(); max_len];
-		wrap_err(unsafe { nfc_initiator_list_passive_targets(self.ptr, modulation.into(), targets.as_mut_ptr(), targets.len()) })?;
-		targets.into_iter().map(|target| target.try_into()).collect()
+		let count = call.check(wrap_err_usize(unsafe { nfc_initiator_list_passive_targets(self.ptr, modulation.into(), targets.as_mut_ptr(), targets.len()) }))?;
+		targets.truncate(count);
+		let targets = call.check(targets.into_iter().map(|target| target.try_into()).collect())?;
+		self.modulation = Some(*modulation);
+		Ok(targets)
 	}
 
 	pub fn initiator_poll_target(&mut self, modulations: &[Modulation], max_polls: u8, poll_period: Duration) -> Result<Target> {
+		let mut call = self.call_with_modulation("initiator_poll_target", modulations.first());
 		let mut target: nfc1_sys::nfc_target = (&Target::new_iso14443a()).into();
 		let modulations: Vec<nfc1_sys::nfc_modulation> = modulations.iter().map(|modulation| modulation.into()).collect();
 		let period = (poll_period.as_millis() as f32 / 150.0).floor().min(255.0) as u8;
-		wrap_err(unsafe { nfc_initiator_poll_target(self.ptr, modulations.as_ptr(), modulations.len(), max_polls, period, &mut target) })?;
-		target.try_into()
+		call.check(wrap_err(unsafe { nfc_initiator_poll_target(self.ptr, modulations.as_ptr(), modulations.len(), max_polls, period, &mut target) }))?;
+		let target: Target = call.check(target.try_into())?;
+		call.set_modulation(target.modulation);
+		self.selected(Ok(target))
 	}
 
 	pub fn initiator_select_dep_target(&mut self, dep_mode: DepMode, baud_rate: BaudRate, initiator: &Dep, timeout: Timeout) -> Result<Target> {
+		let call = self.call_with_modulation("initiator_select_dep_target", Some(&Modulation{ modulation_type: ModulationType::Dep, baud_rate }));
 		let mut target: nfc1_sys::nfc_target = (&Target::new_dep()).into();
 		let initiator: nfc1_sys::nfc_dep_info = initiator.into();
-		wrap_err(unsafe { nfc_initiator_select_dep_target(self.ptr, dep_mode.into(), baud_rate.into(), &initiator, &mut target, timeout.into()) })?;
-		target.try_into()
+		call.check(wrap_err(unsafe { nfc_initiator_select_dep_target(self.ptr, dep_mode.into(), baud_rate.into(), &initiator, &mut target, timeout.into()) }))?;
+		self.selected(call.check(target.try_into()))
 	}
 
 	pub fn initiator_poll_dep_target(&mut self, dep_mode: DepMode, baud_rate: BaudRate, initiator: &Dep, timeout: Timeout) -> Result<Target> {
+		let call = self.call_with_modulation("initiator_poll_dep_target", Some(&Modulation{ modulation_type: ModulationType::Dep, baud_rate }));
 		let mut target: nfc1_sys::nfc_target = (&Target::new_dep()).into();
 		let initiator: nfc1_sys::nfc_dep_info = initiator.into();
-		wrap_err(unsafe { nfc_initiator_poll_dep_target(self.ptr, dep_mode.into(), baud_rate.into(), &initiator, &mut target, timeout.into()) })?;
-		target.try_into()
+		call.check(wrap_err(unsafe { nfc_initiator_poll_dep_target(self.ptr, dep_mode.into(), baud_rate.into(), &initiator, &mut target, timeout.into()) }))?;
+		self.selected(call.check(target.try_into()))
 	}
 
 	pub fn initiator_deselect_target(&mut self) -> Result<()> {
-		wrap_err(unsafe { nfc_initiator_deselect_target(self.ptr) })
+		self.call("initiator_deselect_target").check(wrap_err(unsafe { nfc_initiator_deselect_target(self.ptr) }))?;
+		self.modulation = None;
+		Ok(())
 	}
 
 	pub fn initiator_transceive_bytes(&mut self, tx: &[u8], mut rx_len: usize, timeout: Timeout) -> Result<Vec<u8>> {
+		let call = self.call("initiator_transceive_bytes");
+		call.tx(tx);
 		let mut rx_buf = vec![0u8; rx_len];
-		rx_len = wrap_err_usize(unsafe { nfc_initiator_transceive_bytes(self.ptr, tx.as_ptr(), tx.len(), rx_buf.as_mut_ptr(), rx_buf.len(), timeout.into()) })?;
+		rx_len = call.check(wrap_err_usize(unsafe { nfc_initiator_transceive_bytes(self.ptr, tx.as_ptr(), tx.len(), rx_buf.as_mut_ptr(), rx_buf.len(), timeout.into()) }))?;
 		rx_buf.resize(rx_len, 0u8);
+		call.rx(&rx_buf);
 		Ok(rx_buf)
 	}
 
 	pub fn initiator_transceive_bytes_timed(&mut self, tx: &[u8], mut rx_len: usize) -> Result<(Vec<u8>, u32)> {
+		let call = self.call("initiator_transceive_bytes_timed");
+		call.tx(tx);
 		let mut rx_buf = vec![0u8; rx_len];
 		let mut cycles = 0u32;
-		rx_len = wrap_err_usize(unsafe { nfc_initiator_transceive_bytes_timed(self.ptr, tx.as_ptr(), tx.len(), rx_buf.as_mut_ptr(), rx_buf.len(), &mut cycles) })?;
+		rx_len = call.check(wrap_err_usize(unsafe { nfc_initiator_transceive_bytes_timed(self.ptr, tx.as_ptr(), tx.len(), rx_buf.as_mut_ptr(), rx_buf.len(), &mut cycles) }))?;
 		rx_buf.resize(rx_len, 0u8);
+		call.rx(&rx_buf);
 		Ok((rx_buf, cycles))
 	}
 
 	pub fn initiator_transceive_bits(&mut self, tx: &[u8], tx_bits: usize, mut rx_len: usize) -> Result<Vec<u8>> {
+		let call = self.call("initiator_transceive_bits");
 		if tx_bits > tx.len() * 8 {
-			return Err(Error::BufferOverflow);
+			return call.check(Err(Error::BufferOverflow));
 		}
+		call.tx_bits(tx, tx_bits, None);
 		let mut rx_buf = vec![0u8; rx_len];
-		rx_len = wrap_err_usize(unsafe { nfc_initiator_transceive_bits(self.ptr, tx.as_ptr(), tx_bits, ptr::null(), rx_buf.as_mut_ptr(), rx_buf.len(), ptr::null_mut()) })?;
+		rx_len = call.check(wrap_err_usize(unsafe { nfc_initiator_transceive_bits(self.ptr, tx.as_ptr(), tx_bits, ptr::null(), rx_buf.as_mut_ptr(), rx_buf.len(), ptr::null_mut()) }))?;
 		rx_buf.resize(rx_len, 0u8);
+		call.rx_bits(&rx_buf, rx_len, None);
 		Ok(rx_buf)
 	}
 
 	pub fn initiator_transceive_bits_with_parity(&mut self, tx: &[u8], tx_bits: usize, parity_tx: &[u8], mut rx_len: usize) -> Result<(Vec<u8>, Vec<u8>)> {
+		let call = self.call("initiator_transceive_bits_with_parity");
 		if tx_bits > tx.len() * 8 {
-			return Err(Error::BufferOverflow);
+			return call.check(Err(Error::BufferOverflow));
 		}
+		call.tx_bits(tx, tx_bits, Some(parity_tx));
 		let mut rx_buf = vec![0u8; rx_len];
 		let mut rx_parity_buf = vec![0u8; rx_len];
-		rx_len = wrap_err_usize(unsafe { nfc_initiator_transceive_bits(self.ptr, tx.as_ptr(), tx_bits, parity_tx.as_ptr(), rx_buf.as_mut_ptr(), rx_buf.len(), rx_parity_buf.as_mut_ptr()) })?;
+		rx_len = call.check(wrap_err_usize(unsafe { nfc_initiator_transceive_bits(self.ptr, tx.as_ptr(), tx_bits, parity_tx.as_ptr(), rx_buf.as_mut_ptr(), rx_buf.len(), rx_parity_buf.as_mut_ptr()) }))?;
 		rx_buf.resize(rx_len, 0u8);
 		rx_parity_buf.resize(rx_len, 0u8);
+		call.rx_bits(&rx_buf, rx_len, Some(&rx_parity_buf));
 		Ok((rx_buf, rx_parity_buf))
 	}
 
 	pub fn initiator_transceive_bits_timed(&mut self, tx: &[u8], tx_bits: usize, mut rx_len: usize) -> Result<(Vec<u8>, u32)> {
+		let call = self.call("initiator_transceive_bits_timed");
 		if tx_bits > tx.len() * 8 {
-			return Err(Error::BufferOverflow);
+			return call.check(Err(Error::BufferOverflow));
 		}
+		call.tx_bits(tx, tx_bits, None);
 		let mut rx_buf = vec![0u8; rx_len];
 		let mut cycles = 0u32;
-		rx_len = wrap_err_usize(unsafe { nfc_initiator_transceive_bits_timed(self.ptr, tx.as_ptr(), tx_bits, ptr::null(), rx_buf.as_mut_ptr(), rx_buf.len(), ptr::null_mut(), &mut cycles) })?;
+		rx_len = call.check(wrap_err_usize(unsafe { nfc_initiator_transceive_bits_timed(self.ptr, tx.as_ptr(), tx_bits, ptr::null(), rx_buf.as_mut_ptr(), rx_buf.len(), ptr::null_mut(), &mut cycles) }))?;
 		rx_buf.resize(rx_len, 0u8);
+		call.rx_bits(&rx_buf, rx_len, None);
 		Ok((rx_buf, cycles))
 	}
 
 	pub fn initiator_transceive_bits_with_parity_timed(&mut self, tx: &[u8], tx_bits: usize, parity_tx: &[u8], mut rx_len: usize) -> Result<(Vec<u8>, Vec<u8>, u32)> {
+		let call = self.call("initiator_transceive_bits_with_parity_timed");
 		if tx_bits > tx.len() * 8 {
-			return Err(Error::BufferOverflow);
+			return call.check(Err(Error::BufferOverflow));
 		}
+		call.tx_bits(tx, tx_bits, Some(parity_tx));
 		let mut rx_buf = vec![0u8; rx_len];
 		let mut rx_parity_buf = vec![0u8; rx_len];
 		let mut cycles = 0u32;
-		rx_len = wrap_err_usize(unsafe { nfc_initiator_transceive_bits_timed(self.ptr, tx.as_ptr(), tx_bits, parity_tx.as_ptr(), rx_buf.as_mut_ptr(), rx_buf.len(), rx_parity_buf.as_mut_ptr(), &mut cycles) })?;
+		rx_len = call.check(wrap_err_usize(unsafe { nfc_initiator_transceive_bits_timed(self.ptr, tx.as_ptr(), tx_bits, parity_tx.as_ptr(), rx_buf.as_mut_ptr(), rx_buf.len(), rx_parity_buf.as_mut_ptr(), &mut cycles) }))?;
 		rx_buf.resize(rx_len, 0u8);
 		rx_parity_buf.resize(rx_len, 0u8);
+		call.rx_bits(&rx_buf, rx_len, Some(&rx_parity_buf));
 		Ok((rx_buf, rx_parity_buf, cycles))
 	}
 
 	pub fn initiator_target_is_present(&mut self, target: &Target) -> Result<()> {
 		let target: nfc1_sys::nfc_target = target.into();
-		wrap_err(unsafe { nfc_initiator_target_is_present(self.ptr, &target) })
+		self.call("initiator_target_is_present").check(wrap_err(unsafe { nfc_initiator_target_is_present(self.ptr, &target) }))
 	}
 
 	pub fn initiator_target_is_present_any(&mut self) -> Result<()> {
-		wrap_err(unsafe { nfc_initiator_target_is_present(self.ptr, ptr::null()) })
+		self.call("initiator_target_is_present_any").check(wrap_err(unsafe { nfc_initiator_target_is_present(self.ptr, ptr::null()) }))
 	}
 
 	// NFC target: act as tag (i.e. MIFARE Classic) or NFC target device.
 
 	pub fn target_init(&mut self, target: &Target, mut rx_len: usize, timeout: Timeout) -> Result<Vec<u8>> {
+		let call = self.call_with_modulation("target_init", Some(&target.modulation));
+		let modulation = target.modulation;
 		let mut target: nfc1_sys::nfc_target = target.into();
 		let mut rx_buf = vec![0u8; rx_len];
-		rx_len = wrap_err_usize(unsafe { nfc_target_init(self.ptr, &mut target, rx_buf.as_mut_ptr(), rx_buf.len(), timeout.into()) })?;
+		rx_len = call.check(wrap_err_usize(unsafe { nfc_target_init(self.ptr, &mut target, rx_buf.as_mut_ptr(), rx_buf.len(), timeout.into()) }))?;
 		rx_buf.resize(rx_len, 0u8);
+		call.rx(&rx_buf);
+		self.modulation = Some(modulation);
 		Ok(rx_buf)
 	}
 
 	pub fn target_send_bytes(&mut self, tx: &[u8], timeout: Timeout) -> Result<()> {
-		wrap_err(unsafe { nfc_target_send_bytes(self.ptr, tx.as_ptr(), tx.len(), timeout.into()) })
+		let call = self.call("target_send_bytes");
+		call.tx(tx);
+		call.check(wrap_err(unsafe { nfc_target_send_bytes(self.ptr, tx.as_ptr(), tx.len(), timeout.into()) }))
 	}
 
 	pub fn target_receive_bytes(&mut self, mut rx_len: usize, timeout: Timeout) -> Result<Vec<u8>> {
+		let call = self.call("target_receive_bytes");
 		let mut rx_buf = vec![0u8; rx_len];
-		rx_len = wrap_err_usize(unsafe { nfc_target_receive_bytes(self.ptr, rx_buf.as_mut_ptr(), rx_buf.len(), timeout.into()) })?;
+		rx_len = call.check(wrap_err_usize(unsafe { nfc_target_receive_bytes(self.ptr, rx_buf.as_mut_ptr(), rx_buf.len(), timeout.into()) }))?;
 		rx_buf.resize(rx_len, 0u8);
+		call.rx(&rx_buf);
 		Ok(rx_buf)
 	}
 
 	pub fn target_send_bits(&mut self, tx: &[u8], tx_bits: usize) -> Result<()> {
+		let call = self.call("target_send_bits");
 		if tx_bits > tx.len() * 8 {
-			return Err(Error::BufferOverflow);
+			return call.check(Err(Error::BufferOverflow));
 		}
-		wrap_err(unsafe { nfc_target_send_bits(self.ptr, tx.as_ptr(), tx_bits, ptr::null_mut()) })
+		call.tx_bits(tx, tx_bits, None);
+		call.check(wrap_err(unsafe { nfc_target_send_bits(self.ptr, tx.as_ptr(), tx_bits, ptr::null_mut()) }))
 	}
 
 	pub fn target_send_bits_with_parity(&mut self, tx: &[u8], tx_bits: usize, parity_tx: &[u8]) -> Result<()> {
+		let call = self.call("target_send_bits_with_parity");
 		if tx_bits > tx.len() * 8 {
-			return Err(Error::BufferOverflow);
+			return call.check(Err(Error::BufferOverflow));
 		}
-		wrap_err(unsafe { nfc_target_send_bits(self.ptr, tx.as_ptr(), tx_bits, parity_tx.as_ptr()) })
+		call.tx_bits(tx, tx_bits, Some(parity_tx));
+		call.check(wrap_err(unsafe { nfc_target_send_bits(self.ptr, tx.as_ptr(), tx_bits, parity_tx.as_ptr()) }))
 	}
 
 	pub fn target_receive_bits(&mut self, mut rx_len: usize) -> Result<Vec<u8>> {
+		let call = self.call("target_receive_bits");
 		let mut rx_buf = vec![0u8; rx_len];
-		rx_len = wrap_err_usize(unsafe { nfc_target_receive_bits(self.ptr, rx_buf.as_mut_ptr(), rx_buf.len(), ptr::null_mut()) })?;
+		rx_len = call.check(wrap_err_usize(unsafe { nfc_target_receive_bits(self.ptr, rx_buf.as_mut_ptr(), rx_buf.len(), ptr::null_mut()) }))?;
 		rx_buf.resize(rx_len, 0u8);
+		call.rx_bits(&rx_buf, rx_len, None);
 		Ok(rx_buf)
 	}
 
 	pub fn target_receive_bits_with_parity(&mut self, mut rx_len: usize) -> Result<(Vec<u8>, Vec<u8>)> {
+		let call = self.call("target_receive_bits_with_parity");
 		let mut rx_buf = vec![0u8; rx_len];
 		let mut rx_parity_buf = vec![0u8; rx_len];
-		rx_len = wrap_err_usize(unsafe { nfc_target_receive_bits(self.ptr, rx_buf.as_mut_ptr(), rx_buf.len(), rx_parity_buf.as_mut_ptr()) })?;
+		rx_len = call.check(wrap_err_usize(unsafe { nfc_target_receive_bits(self.ptr, rx_buf.as_mut_ptr(), rx_buf.len(), rx_parity_buf.as_mut_ptr()) }))?;
 		rx_buf.resize(rx_len, 0u8);
 		rx_parity_buf.resize(rx_len, 0u8);
+		call.rx_bits(&rx_buf, rx_len, Some(&rx_parity_buf));
 		Ok((rx_buf, rx_parity_buf))
 	}
 
@@ -332,7 +431,7 @@ impl Device {
 
 	pub fn get_supported_modulation(&mut self, mode: Mode) -> Result<Vec<ModulationType>> {
 		let mut supported_mt = MaybeUninit::uninit();
-		wrap_err(unsafe { nfc_device_get_supported_modulation(self.ptr, mode.into(), supported_mt.as_mut_ptr()) })?;
+		self.call("get_supported_modulation").check(wrap_err(unsafe { nfc_device_get_supported_modulation(self.ptr, mode.into(), supported_mt.as_mut_ptr()) }))?;
 		unsafe {
 			// XXX: This should be safe, as nfc_device_get_supported_modulation should
 			// return a non-zero error code if supported_mt is not set
@@ -350,10 +449,11 @@ impl Device {
 	}
 
 	pub fn get_supported_baud_rate(&mut self, mode: Mode, modulation_type: ModulationType) -> Result<Vec<BaudRate>> {
+		let call = self.call("get_supported_baud_rate");
 		let mut supported_br = MaybeUninit::uninit();
 		match mode {
-			Mode::Initiator => wrap_err(unsafe { nfc_device_get_supported_baud_rate(self.ptr, modulation_type.into(), supported_br.as_mut_ptr()) })?,
-			Mode::Target => wrap_err(unsafe { nfc_device_get_supported_baud_rate_target_mode(self.ptr, modulation_type.into(), supported_br.as_mut_ptr()) })?,
+			Mode::Initiator => call.check(wrap_err(unsafe { nfc_device_get_supported_baud_rate(self.ptr, modulation_type.into(), supported_br.as_mut_ptr()) }))?,
+			Mode::Target => call.check(wrap_err(unsafe { nfc_device_get_supported_baud_rate_target_mode(self.ptr, modulation_type.into(), supported_br.as_mut_ptr()) }))?,
 		}
 		unsafe {
 			// XXX: This should be safe, as nfc_device_get_supported_baud_rate should
@@ -371,25 +471,64 @@ impl Device {
 		}
 	}
 
+	/// Queries the supported modulations and baud rates in both modes.
+	///
+	/// A mode which the device does not support at all is reported without modulations.
+	pub fn capabilities(&mut self) -> Result<Capabilities> {
+		let mut modulations = |mode: Mode| -> Result<Vec<Modulation>> {
+			let modulation_types = match self.get_supported_modulation(mode) {
+				Err(Error::DeviceNotSupported) | Err(Error::NotImplemented) => return Ok(vec![]),
+				res => res?,
+			};
+			let mut modulations = vec![];
+			for modulation_type in modulation_types {
+				for baud_rate in self.get_supported_baud_rate(mode, modulation_type)? {
+					modulations.push(Modulation{ modulation_type, baud_rate });
+				}
+			}
+			Ok(modulations)
+		};
+		let initiator = modulations(Mode::Initiator)?;
+		let target = modulations(Mode::Target)?;
+
+		let connstring = self.connstring.to_string();
+		Ok(Capabilities{
+			name: self.name().to_string(),
+			driver: connstring.split(':').next().unwrap_or_default().to_string(),
+			connstring,
+			initiator,
+			target,
+		})
+	}
+
 	// Properties accessors
 
 	pub fn set_property_int(&mut self, property: Property, value: i32) -> Result<()> {
-		wrap_err(unsafe { nfc_device_set_property_int(self.ptr, property.into(), value as c_int) })
+		self.call("set_property_int").check(wrap_err(unsafe { nfc_device_set_property_int(self.ptr, property.into(), value as c_int) }))
 	}
 
 	pub fn set_property_bool(&mut self, property: Property, value: bool) -> Result<()> {
-		wrap_err(unsafe { nfc_device_set_property_bool(self.ptr, property.into(), value) })
+		self.call("set_property_bool").check(wrap_err(unsafe { nfc_device_set_property_bool(self.ptr, property.into(), value) }))?;
+		if property == Property::HandleCrc {
+			self.handle_crc = value;
+		}
+		Ok(())
 	}
 
 	// Misc. functions
 
 	pub fn get_information_about(&mut self) -> Result<String> {
 		let mut strinfo_ptr: *mut c_char = ptr::null_mut();
-		wrap_err(unsafe { nfc_device_get_information_about(self.ptr, &mut strinfo_ptr) })?;
+		self.call("get_information_about").check(wrap_err(unsafe { nfc_device_get_information_about(self.ptr, &mut strinfo_ptr) }))?;
 		let strinfo = unsafe { CStr::from_ptr(strinfo_ptr) }.to_string_lossy().into_owned();
 		unsafe { nfc_free(strinfo_ptr as *mut c_void); }
 		Ok(strinfo)
 	}
+
+	/// Returns `get_information_about`, parsed into chip model, firmware version and supported modulations
+	pub fn information(&mut self) -> Result<DeviceInformation> {
+		Ok(DeviceInformation::parse(&self.get_information_about()?))
+	}
 }
 
 impl Drop for Device {
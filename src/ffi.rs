@@ -0,0 +1,196 @@
+// Indirection over the libnfc functions used by this crate.
+//
+// By default these are the functions linked in by nfc1-sys. With the `dlopen`
+// feature, libnfc is instead loaded at runtime and every function is resolved
+// from the loaded library, so that a missing library or symbol is reported as
+// an `Error` rather than as a dynamic linker failure at startup.
+
+#[cfg(feature = "dlopen")]
+use crate::{Error, Result};
+#[allow(unused_imports)]
+use nfc1_sys::{
+	nfc_baud_rate,
+	nfc_connstring,
+	nfc_context,
+	nfc_dep_info,
+	nfc_dep_mode,
+	nfc_device,
+	nfc_mode,
+	nfc_modulation,
+	nfc_modulation_type,
+	nfc_property,
+	nfc_target,
+};
+#[allow(unused_imports)]
+use std::os::raw::{c_char, c_int, c_void};
+#[cfg(feature = "dlopen")]
+use std::ffi::{CStr, OsStr, OsString};
+#[cfg(feature = "dlopen")]
+use std::sync::OnceLock;
+
+/// Minimum libnfc version whose structure layout matches the nfc1-sys bindings
+#[cfg(feature = "dlopen")]
+const MIN_VERSION: (u32, u32) = (1, 8);
+
+#[cfg(all(feature = "dlopen", target_os = "windows"))]
+const LIBRARY_NAMES: &[&str] = &["libnfc.dll", "nfc.dll"];
+#[cfg(all(feature = "dlopen", target_os = "macos"))]
+const LIBRARY_NAMES: &[&str] = &["libnfc.6.dylib", "libnfc.dylib"];
+#[cfg(all(feature = "dlopen", not(target_os = "windows"), not(target_os = "macos")))]
+const LIBRARY_NAMES: &[&str] = &["libnfc.so.6", "libnfc.so"];
+
+macro_rules! ffi_functions {
+	($(
+		$(#[$meta:meta])*
+		fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?;
+	)*) => {
+		$(
+			$(#[$meta])*
+			#[cfg(not(feature = "dlopen"))]
+			pub(crate) use nfc1_sys::$name;
+		)*
+
+		#[cfg(feature = "dlopen")]
+		struct Symbols {
+			$(
+				$(#[$meta])*
+				$name: unsafe extern "C" fn($($ty),*) $(-> $ret)?,
+			)*
+		}
+
+		#[cfg(feature = "dlopen")]
+		impl Symbols {
+			unsafe fn resolve(library: &libloading::Library) -> Result<Self> {
+				Ok(Self{
+					$(
+						$(#[$meta])*
+						$name: *library.get(concat!(stringify!($name), "\0").as_bytes()).map_err(|_| Error::SymbolNotFound)?,
+					)*
+				})
+			}
+		}
+
+		$(
+			$(#[$meta])*
+			#[cfg(feature = "dlopen")]
+			#[allow(clippy::too_many_arguments)]
+			pub(crate) unsafe fn $name($($arg: $ty),*) $(-> $ret)? {
+				(symbols().$name)($($arg),*)
+			}
+		)*
+	};
+}
+
+ffi_functions! {
+	fn nfc_init(context: *mut *mut nfc_context);
+	fn nfc_context_new() -> *mut nfc_context;
+	fn nfc_context_free(context: *mut nfc_context);
+	fn nfc_list_devices(context: *mut nfc_context, connstrings: *mut nfc_connstring, connstrings_len: usize) -> usize;
+	fn nfc_version() -> *const c_char;
+	fn nfc_free(p: *mut c_void);
+
+	fn nfc_open(context: *mut nfc_context, connstring: *const c_char) -> *mut nfc_device;
+	fn nfc_close(pnd: *mut nfc_device);
+	fn nfc_strerror(pnd: *const nfc_device) -> *const c_char;
+	fn nfc_device_get_last_error(pnd: *const nfc_device) -> c_int;
+	fn nfc_abort_command(pnd: *mut nfc_device) -> c_int;
+	fn nfc_idle(pnd: *mut nfc_device) -> c_int;
+	fn nfc_initiator_init(pnd: *mut nfc_device) -> c_int;
+	fn nfc_initiator_init_secure_element(pnd: *mut nfc_device) -> c_int;
+	fn nfc_initiator_select_passive_target(pnd: *mut nfc_device, nm: nfc_modulation, init_data: *const u8, init_data_len: usize, pnt: *mut nfc_target) -> c_int;
+	fn nfc_initiator_list_passive_targets(pnd: *mut nfc_device, nm: nfc_modulation, ant: *mut nfc_target, targets_len: usize) -> c_int;
+	fn nfc_initiator_poll_target(pnd: *mut nfc_device, modulations: *const nfc_modulation, modulations_len: usize, poll_nr: u8, period: u8, pnt: *mut nfc_target) -> c_int;
+	fn nfc_initiator_select_dep_target(pnd: *mut nfc_device, ndm: nfc_dep_mode, nbr: nfc_baud_rate, initiator: *const nfc_dep_info, pnt: *mut nfc_target, timeout: c_int) -> c_int;
+	fn nfc_initiator_poll_dep_target(pnd: *mut nfc_device, ndm: nfc_dep_mode, nbr: nfc_baud_rate, initiator: *const nfc_dep_info, pnt: *mut nfc_target, timeout: c_int) -> c_int;
+	fn nfc_initiator_deselect_target(pnd: *mut nfc_device) -> c_int;
+	fn nfc_initiator_transceive_bytes(pnd: *mut nfc_device, tx: *const u8, tx_len: usize, rx: *mut u8, rx_len: usize, timeout: c_int) -> c_int;
+	fn nfc_initiator_transceive_bits(pnd: *mut nfc_device, tx: *const u8, tx_bits: usize, tx_parity: *const u8, rx: *mut u8, rx_len: usize, rx_parity: *mut u8) -> c_int;
+	fn nfc_initiator_transceive_bytes_timed(pnd: *mut nfc_device, tx: *const u8, tx_len: usize, rx: *mut u8, rx_len: usize, cycles: *mut u32) -> c_int;
+	fn nfc_initiator_transceive_bits_timed(pnd: *mut nfc_device, tx: *const u8, tx_bits: usize, tx_parity: *const u8, rx: *mut u8, rx_len: usize, rx_parity: *mut u8, cycles: *mut u32) -> c_int;
+	fn nfc_initiator_target_is_present(pnd: *mut nfc_device, pnt: *const nfc_target) -> c_int;
+	fn nfc_target_init(pnd: *mut nfc_device, pnt: *mut nfc_target, rx: *mut u8, rx_len: usize, timeout: c_int) -> c_int;
+	fn nfc_target_send_bytes(pnd: *mut nfc_device, tx: *const u8, tx_len: usize, timeout: c_int) -> c_int;
+	fn nfc_target_receive_bytes(pnd: *mut nfc_device, rx: *mut u8, rx_len: usize, timeout: c_int) -> c_int;
+	fn nfc_target_send_bits(pnd: *mut nfc_device, tx: *const u8, tx_bits: usize, tx_parity: *const u8) -> c_int;
+	fn nfc_target_receive_bits(pnd: *mut nfc_device, rx: *mut u8, rx_len: usize, rx_parity: *mut u8) -> c_int;
+	fn nfc_device_get_name(pnd: *mut nfc_device) -> *const c_char;
+	fn nfc_device_get_connstring(pnd: *mut nfc_device) -> *const c_char;
+	fn nfc_device_get_supported_modulation(pnd: *mut nfc_device, mode: nfc_mode, supported_mt: *mut *const nfc_modulation_type) -> c_int;
+	fn nfc_device_get_supported_baud_rate(pnd: *mut nfc_device, nmt: nfc_modulation_type, supported_br: *mut *const nfc_baud_rate) -> c_int;
+	fn nfc_device_get_supported_baud_rate_target_mode(pnd: *mut nfc_device, nmt: nfc_modulation_type, supported_br: *mut *const nfc_baud_rate) -> c_int;
+	fn nfc_device_set_property_int(pnd: *mut nfc_device, property: nfc_property, value: c_int) -> c_int;
+	fn nfc_device_set_property_bool(pnd: *mut nfc_device, property: nfc_property, enable: bool) -> c_int;
+	fn nfc_device_get_information_about(pnd: *mut nfc_device, buf: *mut *mut c_char) -> c_int;
+
+	#[cfg(feature = "driver_pn53x_usb")]
+	fn pn53x_transceive(pnd: *mut nfc_device, tx: *const u8, tx_len: usize, rx: *mut u8, rx_len: usize, timeout: c_int) -> c_int;
+	#[cfg(feature = "driver_pn53x_usb")]
+	fn pn53x_read_register(pnd: *mut nfc_device, register: u16, value: *mut u8) -> c_int;
+	#[cfg(feature = "driver_pn53x_usb")]
+	fn pn53x_write_register(pnd: *mut nfc_device, register: u16, symbol_mask: u8, value: u8) -> c_int;
+
+	fn str_nfc_target(buf: *mut *mut c_char, pnt: *const nfc_target, verbose: bool) -> c_int;
+}
+
+#[cfg(feature = "dlopen")]
+struct Library {
+	// Kept alive for as long as the resolved symbols are used, which is forever
+	_library: libloading::Library,
+	symbols: Symbols,
+}
+
+#[cfg(feature = "dlopen")]
+static LIBRARY: OnceLock<Result<Library>> = OnceLock::new();
+
+#[cfg(feature = "dlopen")]
+fn parse_version(version: &str) -> Option<(u32, u32)> {
+	let mut parts = version.split(|c: char| !c.is_ascii_digit());
+	let major = parts.next()?.parse().ok()?;
+	let minor = parts.next()?.parse().ok()?;
+	Some((major, minor))
+}
+
+#[cfg(feature = "dlopen")]
+fn open(paths: &[OsString]) -> Result<Library> {
+	let library = paths.iter()
+		.find_map(|path| unsafe { libloading::Library::new(path) }.ok())
+		.ok_or(Error::LibraryNotFound)?;
+	let symbols = unsafe { Symbols::resolve(&library) }?;
+
+	// XXX: Safe because nfc_version returns a constant string (#define)
+	let version = unsafe { CStr::from_ptr((symbols.nfc_version)()) }.to_string_lossy();
+	match parse_version(&version) {
+		Some(version) if version.0 == MIN_VERSION.0 && version >= MIN_VERSION => {},
+		_ => return Err(Error::UnsupportedLibraryVersion),
+	}
+
+	Ok(Library{ _library: library, symbols })
+}
+
+#[cfg(feature = "dlopen")]
+pub(crate) fn load(paths: Option<&[&OsStr]>) -> Result<()> {
+	let mut opened = false;
+	let library = LIBRARY.get_or_init(|| {
+		opened = true;
+		let paths: Vec<OsString> = match paths {
+			Some(paths) => paths.iter().map(|path| path.to_os_string()).collect(),
+			None => LIBRARY_NAMES.iter().map(OsString::from).collect(),
+		};
+		open(&paths)
+	});
+	// Paths given after an earlier (possibly implicit) load would be silently ignored
+	if paths.is_some() && !opened {
+		return Err(Error::LibraryAlreadyLoaded);
+	}
+	library.as_ref().map(|_| ()).map_err(|err| *err)
+}
+
+// Entry points load libnfc with `load` first, so that a missing library is
+// reported as an error before any function is called
+#[cfg(feature = "dlopen")]
+fn symbols() -> &'static Symbols {
+	match LIBRARY.get() {
+		Some(Ok(library)) => &library.symbols,
+		_ => unreachable!("libnfc function called before loading libnfc"),
+	}
+}
@@ -0,0 +1,263 @@
+//! Historical bytes of an ATS or ATR, as defined in ISO/IEC 7816-4 (8.1.1).
+//!
+//! The category indicator tells how the bytes are coded. Categories 0x00 and
+//! 0x80 hold COMPACT-TLV data objects, which are decoded here; the other
+//! categories are kept as bytes. `HistoricalBytes::fingerprint` recognizes some
+//! well-known patterns without selecting anything on the card.
+
+use crate::redact::Redacted;
+use crate::ParseError;
+use std::fmt;
+
+/// Registered application provider identifier of the PC/SC workgroup
+pub(crate) const PCSC_RID: [u8; 5] = [0xa0, 0x00, 0x00, 0x03, 0x06];
+
+/// Status indicator: life cycle status and status word
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StatusIndicator {
+	/// Card life cycle status byte
+	pub lcs: Option<u8>,
+	/// Status word (SW1-SW2), `90 00` for a card in normal processing
+	pub sw: Option<[u8; 2]>,
+}
+
+/// A COMPACT-TLV data object of the historical bytes
+#[derive(PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DataObject {
+	/// Tag 1
+	CountryCode(Vec<u8>),
+	/// Tag 2
+	IssuerIdentificationNumber(Vec<u8>),
+	/// Tag 3, the supported application selection methods and data object locations
+	CardServiceData(u8),
+	/// Tag 4
+	InitialAccessData(Vec<u8>),
+	/// Tag 5
+	CardIssuerData(Vec<u8>),
+	/// Tag 6, often the chip manufacturer and type
+	PreIssuingData(Vec<u8>),
+	/// Tag 7, the supported selection methods, data coding and command chaining
+	CardCapabilities(Vec<u8>),
+	/// Tag 8
+	StatusIndicator(StatusIndicator),
+	/// Tag F, or the PC/SC application identifier presence indicator (`4F`)
+	ApplicationIdentifier(Vec<u8>),
+	/// A data object with another tag, or an unexpected length
+	Other { tag: u8, value: Vec<u8> },
+}
+
+impl DataObject {
+	fn new(tag: u8, value: &[u8]) -> Self {
+		match (tag, value) {
+			(0x1, _) => Self::CountryCode(value.to_vec()),
+			(0x2, _) => Self::IssuerIdentificationNumber(value.to_vec()),
+			(0x3, [card_service_data]) => Self::CardServiceData(*card_service_data),
+			(0x4, _) => Self::InitialAccessData(value.to_vec()),
+			(0x5, _) => Self::CardIssuerData(value.to_vec()),
+			(0x6, _) => Self::PreIssuingData(value.to_vec()),
+			(0x7, [_, ..]) if value.len() <= 3 => Self::CardCapabilities(value.to_vec()),
+			(0x8, [lcs]) => Self::StatusIndicator(StatusIndicator{ lcs: Some(*lcs), sw: None }),
+			(0x8, [sw1, sw2]) => Self::StatusIndicator(StatusIndicator{ lcs: None, sw: Some([*sw1, *sw2]) }),
+			(0x8, [lcs, sw1, sw2]) => Self::StatusIndicator(StatusIndicator{ lcs: Some(*lcs), sw: Some([*sw1, *sw2]) }),
+			(0xf, _) => Self::ApplicationIdentifier(value.to_vec()),
+			_ => Self::Other{ tag, value: value.to_vec() },
+		}
+	}
+}
+
+impl fmt::Debug for DataObject {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::CountryCode(value) => write!(f, "CountryCode({})", Redacted(value)),
+			Self::IssuerIdentificationNumber(value) => write!(f, "IssuerIdentificationNumber({})", Redacted(value)),
+			Self::CardServiceData(value) => write!(f, "CardServiceData({:02x})", value),
+			Self::InitialAccessData(value) => write!(f, "InitialAccessData({})", Redacted(value)),
+			Self::CardIssuerData(value) => write!(f, "CardIssuerData({})", Redacted(value)),
+			Self::PreIssuingData(value) => write!(f, "PreIssuingData({})", Redacted(value)),
+			Self::CardCapabilities(value) => write!(f, "CardCapabilities({:02x?})", value),
+			Self::StatusIndicator(status) => write!(f, "StatusIndicator({:?})", status),
+			Self::ApplicationIdentifier(value) => write!(f, "ApplicationIdentifier({:02x?})", value),
+			Self::Other{ tag, value } => write!(f, "Other {{ tag: {:x}, value: {} }}", tag, Redacted(value)),
+		}
+	}
+}
+
+/// Decoded historical bytes
+#[derive(PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HistoricalBytes {
+	pub category: u8,
+	/// COMPACT-TLV data objects, for categories 0x00 and 0x80
+	pub objects: Vec<DataObject>,
+	/// Status indicator, from the last three bytes (category 0x00) or its data object (category 0x80)
+	pub status: Option<StatusIndicator>,
+	/// Bytes following the category indicator, for other categories (the DIR data reference for 0x10)
+	pub data: Vec<u8>,
+}
+
+impl fmt::Debug for HistoricalBytes {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("HistoricalBytes")
+			.field("category", &self.category)
+			.field("objects", &self.objects)
+			.field("status", &self.status)
+			.field("data", &Redacted(&self.data))
+			.finish()
+	}
+}
+
+fn parse_compact_tlv(data: &[u8], offset: usize, pcsc: bool) -> Result<Vec<DataObject>, ParseError> {
+	let mut objects = vec![];
+	let mut i = 0;
+	while i < data.len() {
+		let (tag, len, start) = match data[i] {
+			// PC/SC part 3 codes the application identifier as a BER-TLV object
+			0x4f if pcsc && i == 0 => {
+				let len = *data.get(1).ok_or(ParseError::Truncated{ expected: offset + 2, len: offset + data.len() })? as usize;
+				(0xf, len, 2)
+			},
+			byte => (byte >> 4, (byte & 0x0f) as usize, 1),
+		};
+		let value = data.get(i + start..i + start + len).ok_or(ParseError::Truncated{ expected: offset + i + start + len, len: offset + data.len() })?;
+		objects.push(DataObject::new(tag, value));
+		i += start + len;
+	}
+	Ok(objects)
+}
+
+impl HistoricalBytes {
+	pub fn parse(data: &[u8]) -> Result<Self, ParseError> {
+		let Some((&category, rest)) = data.split_first() else { return Err(ParseError::Truncated{ expected: 1, len: 0 }) };
+		let mut historical_bytes = Self{ category, objects: vec![], status: None, data: vec![] };
+		match category {
+			0x00 => {
+				if rest.len() < 3 {
+					return Err(ParseError::Truncated{ expected: 4, len: data.len() });
+				}
+				let (tlv, status) = rest.split_at(rest.len() - 3);
+				historical_bytes.objects = parse_compact_tlv(tlv, 1, false)?;
+				historical_bytes.status = Some(StatusIndicator{ lcs: Some(status[0]), sw: Some([status[1], status[2]]) });
+			},
+			0x80 => {
+				historical_bytes.objects = parse_compact_tlv(rest, 1, true)?;
+				historical_bytes.status = historical_bytes.objects.iter().find_map(|object| match object {
+					DataObject::StatusIndicator(status) => Some(*status),
+					_ => None,
+				});
+			},
+			0x10 if rest.is_empty() => return Err(ParseError::Truncated{ expected: 2, len: 1 }),
+			_ => historical_bytes.data = rest.to_vec(),
+		}
+		Ok(historical_bytes)
+	}
+
+	pub fn card_service_data(&self) -> Option<u8> {
+		self.objects.iter().find_map(|object| match object {
+			DataObject::CardServiceData(value) => Some(*value),
+			_ => None,
+		})
+	}
+
+	pub fn card_capabilities(&self) -> Option<&[u8]> {
+		self.objects.iter().find_map(|object| match object {
+			DataObject::CardCapabilities(value) => Some(&value[..]),
+			_ => None,
+		})
+	}
+
+	pub fn pre_issuing_data(&self) -> Option<&[u8]> {
+		self.objects.iter().find_map(|object| match object {
+			DataObject::PreIssuingData(value) => Some(&value[..]),
+			_ => None,
+		})
+	}
+
+	pub fn application_identifier(&self) -> Option<&[u8]> {
+		self.objects.iter().find_map(|object| match object {
+			DataObject::ApplicationIdentifier(value) => Some(&value[..]),
+			_ => None,
+		})
+	}
+
+	/// Recognizes well-known patterns of the historical bytes
+	pub fn fingerprint(&self) -> Option<Fingerprint> {
+		if let Some([rid @ .., standard, name0, name1]) = self.application_identifier().and_then(|aid| aid.get(..8)) {
+			if rid == PCSC_RID {
+				return Some(Fingerprint::PcscStorageCard{ standard: *standard, card_name: u16::from_be_bytes([*name0, *name1]) });
+			}
+		}
+		match (self.category, &self.data[..]) {
+			// Type identification coding of MIFARE DESFire and MIFARE Plus, see AN10833
+			(0xc1, [_len, chip_type, version, ..]) => Some(Fingerprint::NxpTypeIdentification{ chip_type: *chip_type, version: *version }),
+			_ => {
+				let text: String = std::iter::once(self.category).chain(self.data.iter().copied())
+					.map(|byte| byte as char)
+					.collect();
+				let start = text.find("JCOP")?;
+				let name = text[start..].chars().take_while(|c| c.is_ascii_alphanumeric() || *c == '_').collect();
+				Some(Fingerprint::Jcop(name))
+			},
+		}
+	}
+}
+
+/// Card recognized from its historical bytes
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fingerprint {
+	/// Storage card in the pseudo-ATR of a PC/SC reader (PC/SC part 3), with the standard byte and card name
+	PcscStorageCard { standard: u8, card_name: u16 },
+	/// MIFARE DESFire or MIFARE Plus, with the chip type and version bytes of its type identification coding
+	NxpTypeIdentification { chip_type: u8, version: u8 },
+	/// NXP JCOP smart card, with its name such as `JCOP41V221`
+	Jcop(String),
+}
+
+/// Returns the name of a PC/SC part 3 card name (NN) value
+pub fn pcsc_card_name(card_name: u16) -> Option<&'static str> {
+	Some(match card_name {
+		0x0001 => "MIFARE Classic 1K",
+		0x0002 => "MIFARE Classic 4K",
+		0x0003 => "MIFARE Ultralight",
+		0x0026 => "MIFARE Mini",
+		0x002f => "Jewel",
+		0x0030 => "Topaz",
+		0x0036 => "MIFARE Plus 2K (SL1)",
+		0x0037 => "MIFARE Plus 4K (SL1)",
+		0x0038 => "MIFARE Plus 2K (SL2)",
+		0x0039 => "MIFARE Plus 4K (SL2)",
+		0x003a => "MIFARE Ultralight C",
+		0x003b => "FeliCa",
+		0x003d => "MIFARE Ultralight EV1",
+		_ => return None,
+	})
+}
+
+impl fmt::Display for Fingerprint {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Fingerprint::PcscStorageCard{ card_name, .. } => match pcsc_card_name(*card_name) {
+				Some(name) => write!(f, "{}", name),
+				None => write!(f, "PC/SC storage card {:04x}", card_name),
+			},
+			Fingerprint::NxpTypeIdentification{ chip_type, .. } => {
+				let chip = match chip_type & 0xf0 {
+					0x00 => "NXP virtual card",
+					0x10 => "MIFARE DESFire",
+					0x20 => "MIFARE Plus",
+					_ => "NXP smart card",
+				};
+				match chip_type & 0x0f {
+					0x01 => write!(f, "{} 1K", chip),
+					0x02 => write!(f, "{} 2K", chip),
+					0x03 => write!(f, "{} 4K", chip),
+					0x04 => write!(f, "{} 8K", chip),
+					_ => write!(f, "{}", chip),
+				}
+			},
+			Fingerprint::Jcop(name) => write!(f, "NXP {}", name),
+		}
+	}
+}
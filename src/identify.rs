@@ -0,0 +1,207 @@
+//! Card type identification of ISO/IEC 14443A targets.
+//!
+//! `identify` classifies a target from its ATQA, SAK and ATS, following the
+//! MIFARE type identification procedure (NXP AN10833). Products which answer
+//! alike, such as the MIFARE Ultralight family and NTAG, are told apart by
+//! `refine`, which sends GET_VERSION and probes the MIFARE Ultralight C
+//! authentication.
+
+#[cfg(feature = "libnfc")]
+use crate::{BaudRate, Device, Modulation, ModulationType, Timeout};
+use crate::historical::{Fingerprint, HistoricalBytes};
+use crate::target_info::Iso14443a;
+use crate::{Ats, Error, Result};
+use std::fmt;
+
+const GET_VERSION: u8 = 0x60;
+const AUTHENTICATE: [u8; 2] = [0x1a, 0x00];
+/// Status of a DESFire answer with more data to come, also the first byte of an Ultralight C authentication answer
+const ADDITIONAL_FRAME: u8 = 0xaf;
+
+/// NXP product identified by `identify` or `refine`
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CardType {
+	MifareMini,
+	/// MIFARE Classic 1K, or MIFARE Plus 2K in security level 1 which answers the same
+	MifareClassic1k,
+	MifareClassic4k,
+	MifarePlus2kSl1,
+	MifarePlus4kSl1,
+	MifarePlus2kSl2,
+	MifarePlus4kSl2,
+	MifarePlusSl3,
+	/// MIFARE Ultralight, or any card of its family (Ultralight C and EV1, NTAG) until refined
+	MifareUltralight,
+	MifareUltralightC,
+	MifareUltralightEv1,
+	MifareUltralightNano,
+	Ntag210,
+	Ntag212,
+	Ntag213,
+	Ntag215,
+	Ntag216,
+	Ntag424Dna,
+	/// MIFARE DESFire of unknown generation, or a card answering like one (NTAG 424 DNA) until refined
+	MifareDesfire,
+	MifareDesfireEv1,
+	MifareDesfireEv2,
+	MifareDesfireEv3,
+	/// NXP SmartMX with MIFARE Classic emulation
+	SmartMx,
+	/// NXP JCOP smart card, with its name such as `JCOP41V221`
+	Jcop(String),
+	/// Another ISO/IEC 14443-4 card
+	Iso14443_4,
+	Unknown,
+}
+
+impl fmt::Display for CardType {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let name = match self {
+			CardType::MifareMini => "MIFARE Mini",
+			CardType::MifareClassic1k => "MIFARE Classic 1K",
+			CardType::MifareClassic4k => "MIFARE Classic 4K",
+			CardType::MifarePlus2kSl1 => "MIFARE Plus 2K (SL1)",
+			CardType::MifarePlus4kSl1 => "MIFARE Plus 4K (SL1)",
+			CardType::MifarePlus2kSl2 => "MIFARE Plus 2K (SL2)",
+			CardType::MifarePlus4kSl2 => "MIFARE Plus 4K (SL2)",
+			CardType::MifarePlusSl3 => "MIFARE Plus (SL3)",
+			CardType::MifareUltralight => "MIFARE Ultralight",
+			CardType::MifareUltralightC => "MIFARE Ultralight C",
+			CardType::MifareUltralightEv1 => "MIFARE Ultralight EV1",
+			CardType::MifareUltralightNano => "MIFARE Ultralight Nano",
+			CardType::Ntag210 => "NTAG210",
+			CardType::Ntag212 => "NTAG212",
+			CardType::Ntag213 => "NTAG213",
+			CardType::Ntag215 => "NTAG215",
+			CardType::Ntag216 => "NTAG216",
+			CardType::Ntag424Dna => "NTAG 424 DNA",
+			CardType::MifareDesfire => "MIFARE DESFire",
+			CardType::MifareDesfireEv1 => "MIFARE DESFire EV1",
+			CardType::MifareDesfireEv2 => "MIFARE DESFire EV2",
+			CardType::MifareDesfireEv3 => "MIFARE DESFire EV3",
+			CardType::SmartMx => "NXP SmartMX",
+			CardType::Jcop(name) => return write!(f, "NXP {}", name),
+			CardType::Iso14443_4 => "ISO/IEC 14443-4 card",
+			CardType::Unknown => "Unknown card",
+		};
+		write!(f, "{}", name)
+	}
+}
+
+// Classifies an ISO/IEC 14443-4 card from the historical bytes of its ATS
+fn identify_iso14443_4(info: &Iso14443a, atqa: u16) -> CardType {
+	let historical_bytes = Ats::parse(info.ats()).map(|ats| ats.historical_bytes).unwrap_or_default();
+	let fingerprint = HistoricalBytes::parse(&historical_bytes).ok().and_then(|historical| historical.fingerprint());
+	match (fingerprint, info.sak) {
+		(Some(Fingerprint::NxpTypeIdentification{ chip_type, version }), _) if chip_type & 0xf0 == 0x10 => match version & 0x0f {
+			0x00 => CardType::MifareDesfireEv1,
+			0x01 => CardType::MifareDesfireEv2,
+			0x02 => CardType::MifareDesfireEv3,
+			_ => CardType::MifareDesfire,
+		},
+		(Some(Fingerprint::NxpTypeIdentification{ chip_type, .. }), sak) if chip_type & 0xf0 == 0x20 => match sak {
+			0x28 => CardType::MifarePlus2kSl1,
+			0x38 => CardType::MifarePlus4kSl1,
+			_ => CardType::MifarePlusSl3,
+		},
+		(Some(Fingerprint::Jcop(name)), _) => CardType::Jcop(name),
+		(_, 0x20) if atqa == 0x0304 && historical_bytes == [0x80] => CardType::MifareDesfire,
+		(_, 0x20) if atqa == 0x0004 => CardType::MifarePlusSl3,
+		(_, 0x28) | (_, 0x38) => CardType::SmartMx,
+		_ => CardType::Iso14443_4,
+	}
+}
+
+/// Identifies an ISO/IEC 14443A target from its ATQA, SAK and ATS (NXP AN10833).
+///
+/// Where products answer alike, the family is returned (`MifareUltralight`,
+/// `MifareDesfire`), see `refine`.
+pub fn identify(info: &Iso14443a) -> CardType {
+	// The UID size bits do not tell products apart
+	let atqa = u16::from_be_bytes(info.atqa) & !0x00c0;
+	match (atqa, info.sak) {
+		(_, 0x09) => CardType::MifareMini,
+		(_, 0x08) | (_, 0x88) => CardType::MifareClassic1k,
+		(0x0004, 0x18) => CardType::MifarePlus4kSl1,
+		(_, 0x18) => CardType::MifareClassic4k,
+		(_, 0x10) => CardType::MifarePlus2kSl2,
+		(_, 0x11) => CardType::MifarePlus4kSl2,
+		(_, 0x00) => CardType::MifareUltralight,
+		(_, sak) if sak & 0x20 != 0 => identify_iso14443_4(info, atqa),
+		_ => CardType::Unknown,
+	}
+}
+
+/// Identifies a card from its GET_VERSION answer (`00` or `AF` and 7 bytes of
+/// vendor, product type, subtype, version, storage size and protocol), or
+/// returns `None` for an unknown product
+pub fn from_version(version: &[u8]) -> Option<CardType> {
+	let [_, 0x04, product_type, _subtype, major_version, _minor_version, storage_size, protocol] = *version else { return None };
+	Some(match (product_type, major_version, storage_size, protocol) {
+		(0x01, 0x01, _, _) => CardType::MifareDesfireEv1,
+		(0x01, 0x12, _, _) => CardType::MifareDesfireEv2,
+		(0x01, 0x33, _, _) => CardType::MifareDesfireEv3,
+		(0x01, _, _, _) => CardType::MifareDesfire,
+		(0x02, _, _, _) => CardType::MifarePlusSl3,
+		(0x03, 0x01, _, _) => CardType::MifareUltralightEv1,
+		(0x03, 0x02, _, _) => CardType::MifareUltralightNano,
+		(0x04, 0x30, _, 0x05) => CardType::Ntag424Dna,
+		(0x04, _, 0x0b, 0x03) => CardType::Ntag210,
+		(0x04, _, 0x0e, 0x03) => CardType::Ntag212,
+		(0x04, _, 0x0f, 0x03) => CardType::Ntag213,
+		(0x04, _, 0x11, 0x03) => CardType::Ntag215,
+		(0x04, _, 0x13, 0x03) => CardType::Ntag216,
+		_ => return None,
+	})
+}
+
+// Unsupported commands are answered with a NAK or not at all
+fn answered(result: Result<Vec<u8>>) -> Result<Option<Vec<u8>>> {
+	match result {
+		Ok(answer) => Ok(Some(answer)),
+		Err(Error::Timeout) | Err(Error::RfTransmissionError) | Err(Error::Chip) => Ok(None),
+		Err(err) => Err(err),
+	}
+}
+
+/// Refines a family returned by `identify`, sending commands with `transceive`.
+///
+/// GET_VERSION is sent to the MIFARE Ultralight and MIFARE DESFire families.
+/// An Ultralight which does not support it is probed with the first step of
+/// the Ultralight C authentication. Cards of the Ultralight family halt on a
+/// command they do not support, so `transceive` must select the card again
+/// after a failed command. Other card types are returned unchanged.
+pub fn refine<F: FnMut(&[u8]) -> Result<Vec<u8>>>(card_type: CardType, mut transceive: F) -> Result<CardType> {
+	if card_type != CardType::MifareUltralight && card_type != CardType::MifareDesfire {
+		return Ok(card_type);
+	}
+	if let Some(version) = answered(transceive(&[GET_VERSION]))? {
+		return Ok(from_version(&version).unwrap_or(card_type));
+	}
+	if card_type == CardType::MifareUltralight {
+		if let Some(answer) = answered(transceive(&AUTHENTICATE))? {
+			if answer.len() == 9 && answer[0] == ADDITIONAL_FRAME {
+				return Ok(CardType::MifareUltralightC);
+			}
+		}
+	}
+	Ok(card_type)
+}
+
+#[cfg(feature = "libnfc")]
+impl Device {
+	/// Identifies the selected ISO/IEC 14443A target, see `identify` and `refine`
+	pub fn identify(&mut self, info: &Iso14443a) -> Result<CardType> {
+		let modulation = Modulation{ modulation_type: ModulationType::Iso14443a, baud_rate: BaudRate::Baud106 };
+		let iso14443_4 = info.sak & 0x20 != 0;
+		refine(identify(info), |command| {
+			let result = self.initiator_transceive_bytes(command, 64, Timeout::Default);
+			if result.is_err() && !iso14443_4 {
+				self.initiator_select_passive_target_with_init_data(&modulation, info.uid())?;
+			}
+			result
+		})
+	}
+}
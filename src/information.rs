@@ -0,0 +1,134 @@
+use crate::{BaudRate, Modulation, ModulationType};
+use std::fmt;
+
+/// Version of the firmware of an NFC chip
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FirmwareVersion {
+	pub major: u8,
+	pub minor: u8,
+}
+
+impl fmt::Display for FirmwareVersion {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}.{}", self.major, self.minor)
+	}
+}
+
+/// Structured form of the text returned by `Device::get_information_about`.
+///
+/// The PN53x driver output (`chip: PN533 v2.7`, followed by the initiator and
+/// target mode modulations) is parsed into fields. Output of other drivers is
+/// only available as `raw`, as are lines which are not recognized.
+#[derive(Debug, PartialEq, Eq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeviceInformation {
+	/// Chip model, such as `PN533`
+	pub chip: Option<String>,
+	pub firmware_version: Option<FirmwareVersion>,
+	/// Modulations supported in initiator (reader) mode
+	pub initiator_modulations: Vec<Modulation>,
+	/// Modulations supported in target (emulation) mode
+	pub target_modulations: Vec<Modulation>,
+	/// The unparsed text
+	pub raw: String,
+}
+
+const MODULATION_TYPES: [ModulationType; 10] = [
+	ModulationType::Iso14443a,
+	ModulationType::Jewel,
+	ModulationType::Iso14443b,
+	ModulationType::Iso14443bi,
+	ModulationType::Iso14443b2sr,
+	ModulationType::Iso14443b2ct,
+	ModulationType::Felica,
+	ModulationType::Dep,
+	ModulationType::Barcode,
+	ModulationType::Iso14443biClass,
+];
+
+const BAUD_RATES: [BaudRate; 4] = [BaudRate::Baud106, BaudRate::Baud212, BaudRate::Baud424, BaudRate::Baud847];
+
+// Splits on commas which are not within parentheses
+fn split_top_level(list: &str) -> Vec<&str> {
+	let mut parts = vec![];
+	let (mut depth, mut start) = (0usize, 0);
+	for (i, c) in list.char_indices() {
+		match c {
+			'(' => depth += 1,
+			')' => depth = depth.saturating_sub(1),
+			',' if depth == 0 => {
+				parts.push(list[start..i].trim());
+				start = i + 1;
+			},
+			_ => {},
+		}
+	}
+	parts.push(list[start..].trim());
+	parts.retain(|part| !part.is_empty());
+	parts
+}
+
+// Parses the format of pn53x_get_information_about, such as
+// `ISO/IEC 14443A (106 kbps), FeliCa (424 kbps, 212 kbps)`
+fn parse_modulations(list: &str) -> Option<Vec<Modulation>> {
+	let mut modulations = vec![];
+	for part in split_top_level(list) {
+		// Modulation type names can contain parentheses too, the baud rates are the last group
+		let open = part.rfind(" (")?;
+		let name = &part[..open];
+		let baud_rates = part[open + 2..].strip_suffix(')')?;
+		let modulation_type = *MODULATION_TYPES.iter().find(|modulation_type| modulation_type.to_string() == name)?;
+		for baud_rate in baud_rates.split(',') {
+			let baud_rate = *BAUD_RATES.iter().find(|known| known.to_string() == baud_rate.trim())?;
+			modulations.push(Modulation{ modulation_type, baud_rate });
+		}
+	}
+	Some(modulations)
+}
+
+// Parses `PN533 v2.7`
+fn parse_chip(chip: &str) -> (String, Option<FirmwareVersion>) {
+	let Some((model, version)) = chip.rsplit_once(" v") else { return (chip.to_string(), None) };
+	let version = version.split_once('.').and_then(|(major, minor)| Some(FirmwareVersion{
+		major: major.parse().ok()?,
+		minor: minor.parse().ok()?,
+	}));
+	match version {
+		Some(version) => (model.to_string(), Some(version)),
+		None => (chip.to_string(), None),
+	}
+}
+
+impl DeviceInformation {
+	/// Parses the text returned by `Device::get_information_about`, never failing
+	pub fn parse(text: &str) -> Self {
+		let mut information = Self{
+			chip: None,
+			firmware_version: None,
+			initiator_modulations: vec![],
+			target_modulations: vec![],
+			raw: text.to_string(),
+		};
+		for line in text.lines() {
+			let Some((key, value)) = line.split_once(':') else { continue };
+			let value = value.trim();
+			match key.trim() {
+				"chip" => {
+					let (chip, firmware_version) = parse_chip(value);
+					information.chip = Some(chip);
+					information.firmware_version = firmware_version;
+				},
+				// Sic, libnfc 1.8 misspells initiator
+				"initator mode modulations" | "initiator mode modulations" => {
+					information.initiator_modulations = parse_modulations(value).unwrap_or_default();
+				},
+				"target mode modulations" => {
+					information.target_modulations = parse_modulations(value).unwrap_or_default();
+				},
+				_ => {},
+			}
+		}
+		information
+	}
+}
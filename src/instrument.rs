@@ -0,0 +1,156 @@
+// Instrumentation of `Device` operations.
+//
+// Every `Device` method opens a `Call`, reports the frames it sends and
+// receives, and passes its result through `Call::check`. Frames are only
+// copied when a trace sink is installed, and the `tracing` and `metrics`
+// instrumentation compiles away without their features.
+
+use crate::{Modulation, Result};
+use crate::trace::{Direction, Frame, TraceSink};
+use std::sync::{Arc, Mutex};
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+use std::cell::Cell;
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+use std::time::Instant;
+
+pub(crate) struct Call {
+	#[cfg(feature = "tracing")]
+	span: tracing::span::EnteredSpan,
+	operation: &'static str,
+	#[cfg(feature = "metrics")]
+	connstring: Arc<str>,
+	modulation: Option<Modulation>,
+	trace: Option<(Arc<Mutex<dyn TraceSink>>, bool)>,
+	#[cfg(any(feature = "tracing", feature = "metrics"))]
+	start: Instant,
+	#[cfg(any(feature = "tracing", feature = "metrics"))]
+	failed: Cell<bool>,
+}
+
+#[cfg_attr(not(any(feature = "tracing", feature = "metrics")), allow(unused_variables))]
+impl Call {
+	pub(crate) fn begin(operation: &'static str, connstring: &Arc<str>, modulation: Option<&Modulation>) -> Self {
+		Self{
+			#[cfg(feature = "tracing")]
+			span: tracing::debug_span!(
+				target: "nfc1::device",
+				"nfc",
+				operation,
+				connstring = &**connstring,
+				modulation = modulation.map(tracing::field::display),
+				tx_len = tracing::field::Empty,
+				rx_len = tracing::field::Empty,
+				duration_us = tracing::field::Empty,
+				result = tracing::field::Empty,
+			).entered(),
+			operation,
+			#[cfg(feature = "metrics")]
+			connstring: connstring.clone(),
+			modulation: modulation.copied(),
+			trace: None,
+			#[cfg(any(feature = "tracing", feature = "metrics"))]
+			start: Instant::now(),
+			#[cfg(any(feature = "tracing", feature = "metrics"))]
+			failed: Cell::new(false),
+		}
+	}
+
+	/// Replaces the modulation of this call, once the target has answered
+	pub(crate) fn set_modulation(&mut self, modulation: Modulation) {
+		#[cfg(feature = "tracing")]
+		self.span.record("modulation", tracing::field::display(&modulation));
+		self.modulation = Some(modulation);
+	}
+
+	/// Passes the frames of this call to `sink`, `has_crc` tells whether they include their CRC
+	pub(crate) fn trace(mut self, sink: Arc<Mutex<dyn TraceSink>>, has_crc: bool) -> Self {
+		self.trace = Some((sink, has_crc));
+		self
+	}
+
+	// In target mode, frames are sent by the target rather than by the initiator
+	fn direction(&self, tx: bool) -> Direction {
+		if tx != self.operation.starts_with("target_") {
+			Direction::InitiatorToTarget
+		} else {
+			Direction::TargetToInitiator
+		}
+	}
+
+	/// Records a frame sent by the device
+	pub(crate) fn tx(&self, data: &[u8]) {
+		self.frame(true, data, None, None);
+	}
+
+	/// Records a bit-oriented frame sent by the device
+	pub(crate) fn tx_bits(&self, data: &[u8], bits: usize, parity: Option<&[u8]>) {
+		self.frame(true, data, Some(bits), parity);
+	}
+
+	/// Records a frame received by the device
+	pub(crate) fn rx(&self, data: &[u8]) {
+		self.frame(false, data, None, None);
+	}
+
+	/// Records a bit-oriented frame received by the device
+	pub(crate) fn rx_bits(&self, data: &[u8], bits: usize, parity: Option<&[u8]>) {
+		self.frame(false, data, Some(bits), parity);
+	}
+
+	fn frame(&self, tx: bool, data: &[u8], bits: Option<usize>, parity: Option<&[u8]>) {
+		let len = bits.map_or(data.len(), |bits| data.len().min(bits.div_ceil(8)));
+		let data = &data[..len];
+		let parity = parity.map(|parity| &parity[..parity.len().min(len)]);
+		#[cfg(feature = "tracing")]
+		{
+			self.span.record(if tx { "tx_len" } else { "rx_len" }, data.len());
+			self.span.in_scope(|| tracing::trace!(target: "nfc1::device", len = data.len(), bits, "{}: {}", if tx { "TX" } else { "RX" }, crate::redact::Redacted(data)));
+		}
+		#[cfg(feature = "metrics")]
+		if tx {
+			crate::metrics::record_tx(&self.connstring, data.len());
+		} else {
+			crate::metrics::record_rx(&self.connstring, data.len());
+		}
+		if let Some((sink, has_crc)) = &self.trace {
+			let mut frame = Frame::new(self.direction(tx), data);
+			frame.modulation = self.modulation;
+			frame.bits = bits;
+			frame.parity = parity.map(|parity| parity.to_vec());
+			frame.has_crc = *has_crc;
+			sink.lock().unwrap_or_else(|err| err.into_inner()).frame(&frame);
+		}
+	}
+
+	/// Records the error of a failed operation and passes the result through
+	pub(crate) fn check<T>(&self, result: Result<T>) -> Result<T> {
+		#[cfg(any(feature = "tracing", feature = "metrics"))]
+		if let Err(err) = &result {
+			self.failed.set(true);
+			#[cfg(feature = "tracing")]
+			{
+				self.span.record("result", tracing::field::display(err));
+				self.span.in_scope(|| tracing::debug!(target: "nfc1::device", error = %err, "operation failed"));
+			}
+			#[cfg(feature = "metrics")]
+			crate::metrics::record_error(self.operation, &self.connstring, err);
+		}
+		result
+	}
+}
+
+impl Drop for Call {
+	fn drop(&mut self) {
+		#[cfg(any(feature = "tracing", feature = "metrics"))]
+		let duration = self.start.elapsed();
+		#[cfg(feature = "tracing")]
+		{
+			self.span.record("duration_us", duration.as_micros() as u64);
+			if !self.failed.get() {
+				self.span.record("result", "ok");
+			}
+		}
+		#[cfg(feature = "metrics")]
+		crate::metrics::record_operation(self.operation, &self.connstring, self.modulation.as_ref(), duration);
+	}
+}
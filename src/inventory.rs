@@ -0,0 +1,210 @@
+//! ISO/IEC 14443A anticollision over raw frames, finding every card in the field.
+//!
+//! `Device::initiator_list_passive_targets` is limited by the chip (two
+//! targets for PN53x). The inventory here runs the ISO/IEC 14443-3 sequence
+//! itself: wake the cards, resolve one UID bit by bit through every cascade
+//! level, select it, halt it, and repeat until no card answers.
+//!
+//! Both `Error::Timeout` and `Error::RfTransmissionError` are taken as no
+//! answer, as PN53x devices report silence as the latter. Collisions are
+//! detected from the received frame instead: a parity error (when parity bits
+//! are returned, see `RawSession::with_manual_parity`), a short frame or a
+//! wrong BCC. The number of frames spent on each card and the number of cards
+//! are bounded, so a noisy field can not keep the loop running.
+
+use crate::raw::{CascadeLevel, RawFrame, RawResponse};
+#[cfg(feature = "libnfc")]
+use crate::raw::RawSession;
+use crate::redact::Redacted;
+use crate::{CardId, Error, ModulationType, Result, UidSize};
+use std::fmt;
+
+const REQA: u8 = 0x26;
+const WUPA: u8 = 0x52;
+const HLTA: [u8; 2] = [0x50, 0x00];
+const CASCADE_TAG: u8 = 0x88;
+/// Frames sent to resolve and select one card through all cascade levels
+const MAX_FRAMES_PER_CARD: usize = 256;
+/// Cards found by one inventory
+const MAX_CARDS: usize = 64;
+/// Rounds in which a card answered but could not be resolved, before giving up
+const MAX_FAILED_ROUNDS: usize = 3;
+
+/// A card found by `inventory`
+#[derive(PartialEq, Eq, Clone)]
+pub struct InventoryCard {
+	/// Complete UID, without cascade tags
+	pub uid: Vec<u8>,
+	/// ATQA, unless it collided with the ATQA of another card
+	pub atqa: Option<[u8; 2]>,
+	/// SAK of the last cascade level
+	pub sak: u8,
+}
+
+/// Cards found by `inventory`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Inventory {
+	pub cards: Vec<InventoryCard>,
+	/// Whether the field went silent after the last card was found. Cards may
+	/// be missing if a card could not be resolved, did not halt, or if the
+	/// limit of 64 cards was reached.
+	pub complete: bool,
+}
+
+impl InventoryCard {
+	pub fn uid_size(&self) -> Option<UidSize> {
+		UidSize::from_len(self.uid.len())
+	}
+
+	pub fn card_id(&self) -> CardId {
+		CardId::new(ModulationType::Iso14443a, &self.uid)
+	}
+}
+
+impl fmt::Debug for InventoryCard {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("InventoryCard")
+			.field("uid", &Redacted(&self.uid))
+			.field("atqa", &self.atqa)
+			.field("sak", &self.sak)
+			.finish()
+	}
+}
+
+fn bit(data: &[u8], i: usize) -> u8 {
+	(data[i / 8] >> (i % 8)) & 1
+}
+
+fn set_bit(data: &mut [u8], i: usize, value: u8) {
+	data[i / 8] = (data[i / 8] & !(1 << (i % 8))) | (value << (i % 8));
+}
+
+// No card answering is an expected answer during anticollision
+fn expected<T>(result: Result<T>) -> Result<Option<T>> {
+	match result {
+		Ok(value) => Ok(Some(value)),
+		Err(Error::Timeout) | Err(Error::RfTransmissionError) => Ok(None),
+		Err(err) => Err(err),
+	}
+}
+
+// Selects the card with UID CLn `uid`, returning its SAK, or `None` if no card has it or the SAK is garbled
+fn select<F: FnMut(&RawFrame) -> Result<RawResponse>>(transceive: &mut F, level: CascadeLevel, uid: &[u8; 4]) -> Result<Option<u8>> {
+	let Some(response) = expected(transceive(&RawFrame::select(level, uid)))? else { return Ok(None) };
+	match response.payload() {
+		Some([sak]) if response.parity_valid() => Ok(Some(*sak)),
+		_ => Ok(None),
+	}
+}
+
+// Resolves the UID CLn of one card whose first `known_bits` bits are `prefix`, and selects it.
+//
+// Cards answer ANTICOLLISION with the bits following the prefix, which are
+// returned starting at the first bit of the first byte. On a collision, the
+// prefix is extended by one bit, trying 0 before 1. Every frame sent uses up
+// one of `budget`, and the card is given up when none is left.
+fn resolve<F: FnMut(&RawFrame) -> Result<RawResponse>>(transceive: &mut F, level: CascadeLevel, mut prefix: [u8; 4], known_bits: usize, budget: &mut usize) -> Result<Option<([u8; 4], u8)>> {
+	let Some(remaining) = budget.checked_sub(1) else { return Ok(None) };
+	*budget = remaining;
+	if known_bits == 32 {
+		return Ok(select(transceive, level, &prefix)?.map(|sak| (prefix, sak)));
+	}
+	let Some(response) = expected(transceive(&RawFrame::anticollision(level, &prefix, known_bits)?))? else { return Ok(None) };
+	if response.bits == 40 - known_bits && response.parity_valid() {
+		let mut cln = [0u8; 5];
+		cln[..4].copy_from_slice(&prefix);
+		for i in known_bits..40 {
+			set_bit(&mut cln, i, bit(&response.data, i - known_bits));
+		}
+		let uid = [cln[0], cln[1], cln[2], cln[3]];
+		// A wrong BCC, or no card answering SELECT, means bits of several cards were combined
+		if cln[4] == uid.iter().fold(0, |bcc, byte| bcc ^ byte) {
+			if let Some(sak) = select(transceive, level, &uid)? {
+				return Ok(Some((uid, sak)));
+			}
+		}
+	}
+	for value in [0, 1] {
+		set_bit(&mut prefix, known_bits, value);
+		if let Some(card) = resolve(transceive, level, prefix, known_bits + 1, budget)? {
+			return Ok(Some(card));
+		}
+	}
+	Ok(None)
+}
+
+// Resolves and selects one card through all cascade levels
+fn select_card<F: FnMut(&RawFrame) -> Result<RawResponse>>(transceive: &mut F, atqa: Option<[u8; 2]>) -> Result<Option<InventoryCard>> {
+	let mut uid = vec![];
+	let mut level = CascadeLevel::One;
+	let mut budget = MAX_FRAMES_PER_CARD;
+	loop {
+		let Some((cln, sak)) = resolve(transceive, level, [0; 4], 0, &mut budget)? else { return Ok(None) };
+		// The cascade bit of the SAK tells that the UID is not complete yet
+		if sak & 0x04 == 0 {
+			uid.extend_from_slice(&cln);
+			return Ok(Some(InventoryCard{ uid, atqa, sak }));
+		}
+		if cln[0] != CASCADE_TAG {
+			return Ok(None);
+		}
+		uid.extend_from_slice(&cln[1..]);
+		let Some(next) = level.next() else { return Ok(None) };
+		level = next;
+	}
+}
+
+/// Finds every ISO/IEC 14443A card in the field, sending frames with `transceive`.
+///
+/// Cards are left halted; a card which is still active from an earlier
+/// selection is halted first so that it is found too. A round in which a card
+/// answers but can not be resolved is retried, up to 3 times. Check
+/// `Inventory::complete` to know whether every card was found.
+pub fn inventory<F: FnMut(&RawFrame) -> Result<RawResponse>>(mut transceive: F) -> Result<Inventory> {
+	let mut cards: Vec<InventoryCard> = vec![];
+	let mut failed_rounds = 0;
+	// Whether the last card answering could not be resolved
+	let mut unresolved = false;
+	expected(transceive(&RawFrame::standard(&HLTA)))?;
+	// WUPA also wakes halted cards, later rounds must not find them again
+	let mut wake = WUPA;
+	while cards.len() < MAX_CARDS && failed_rounds < MAX_FAILED_ROUNDS {
+		let Some(response) = expected(transceive(&RawFrame::short(wake)))? else {
+			return Ok(Inventory{ cards, complete: !unresolved });
+		};
+		let atqa = match response.data[..] {
+			[atqa0, atqa1] if response.bits == 16 && response.parity_valid() => Some([atqa0, atqa1]),
+			_ => None,
+		};
+		wake = REQA;
+		let Some(card) = select_card(&mut transceive, atqa)? else {
+			failed_rounds += 1;
+			unresolved = true;
+			continue;
+		};
+		unresolved = false;
+		// Stop if a card does not halt
+		if cards.iter().any(|found| found.uid == card.uid) {
+			break;
+		}
+		cards.push(card);
+		expected(transceive(&RawFrame::standard(&HLTA)))?;
+	}
+	Ok(Inventory{ cards, complete: false })
+}
+
+#[cfg(feature = "libnfc")]
+impl RawSession<'_> {
+	/// Finds every ISO/IEC 14443A card in the field, see `inventory`.
+	///
+	/// The session must be created with `RawSession::with_manual_parity`, as
+	/// devices handling parity report collisions like silence. Otherwise,
+	/// `Error::InvalidArgument` is returned.
+	pub fn inventory(&mut self) -> Result<Inventory> {
+		if self.handles_parity() {
+			return Err(Error::InvalidArgument);
+		}
+		// Longest answer is 40 bits of UID CLn and BCC
+		inventory(|frame| self.transceive(frame, 5))
+	}
+}
@@ -1,37 +1,82 @@
-use nfc1_sys::{
-	nfc_version,
-	str_nfc_baud_rate,
-	str_nfc_modulation_type,
-	iso14443a_crc as nfc_iso14443a_crc,
-	iso14443a_crc_append as nfc_iso14443a_crc_append,
-	iso14443b_crc as nfc_iso14443b_crc,
-	iso14443b_crc_append as nfc_iso14443b_crc_append,
-};
+// The vendored libnfc would be linked in, and a system libnfc loaded next to it
+#[cfg(all(feature = "dlopen", any(
+	feature = "vendored", feature = "logging", feature = "usb_logging", feature = "conffiles", feature = "envvars",
+	feature = "default_drivers", feature = "driver_pcsc", feature = "driver_acr122_pcsc", feature = "driver_acr122_usb",
+	feature = "driver_acr122s", feature = "driver_arygon", feature = "driver_pn532_i2c", feature = "driver_pn532_spi",
+	feature = "driver_pn532_uart", feature = "driver_pn53x_usb", feature = "driver_pn71xx",
+)))]
+compile_error!("the `dlopen` feature loads the system libnfc and can not be combined with features building the vendored libnfc (`vendored`, `driver_*`, `default_drivers`, `logging`, `usb_logging`, `conffiles`, `envvars`, `log`); use `default-features = false`");
+
+#[cfg(feature = "libnfc")]
+use ffi::nfc_version;
+#[cfg(feature = "libnfc")]
 use std::ffi::CStr;
+#[cfg(feature = "dlopen")]
+use std::ffi::OsStr;
 use std::os::raw::c_int;
 use std::time::Duration;
-use std::string::ToString;
 use std::io::{ErrorKind, Error as IoError};
 
+#[cfg(feature = "libnfc")]
+mod ffi;
 mod target;
+mod capabilities;
+mod information;
+#[cfg(feature = "libnfc")]
+mod instrument;
+#[cfg(feature = "libnfc")]
 mod context;
+#[cfg(feature = "libnfc")]
 mod device;
+pub mod atr;
+pub mod ats;
+pub mod atqb;
+pub mod config;
+pub mod crc;
+pub mod historical;
+pub mod identify;
+pub mod inventory;
+pub mod raw;
+pub mod redact;
+pub mod trace;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(all(feature = "log", unix))]
+pub mod logging;
+#[cfg(feature = "serde")]
+mod serialize;
 #[cfg(test)]
 mod test;
 
 pub use target::Target;
+pub use atr::Atr;
+pub use ats::Ats;
+pub use target::id::{CardId, UidSize};
+pub use capabilities::Capabilities;
+pub use information::{DeviceInformation, FirmwareVersion};
+pub use raw::RawFrame;
+#[cfg(feature = "libnfc")]
+pub use raw::RawSession;
+#[cfg(feature = "libnfc")]
 pub use device::Device;
+#[cfg(feature = "libnfc")]
 pub use context::Context;
 pub use target::info as target_info;
 
 /// Safe error type representing the NFC_E* constants
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Error {
 	// rs-nfc1 errors
 	Malloc,
 	Undefined(c_int),
 	UndefinedModulationType,
 	NoDeviceFound,
+	LibraryNotFound,
+	SymbolNotFound,
+	UnsupportedLibraryVersion,
+	LibraryAlreadyLoaded,
+	InvalidCrc,
 
 	// libnfc errors
 	Io,
@@ -49,6 +94,7 @@ pub enum Error {
 	Chip,
 }
 
+#[cfg(feature = "libnfc")]
 impl From<c_int> for Error {
 	fn from(input: c_int) -> Self {
 		match input {
@@ -78,6 +124,11 @@ impl From<Error> for IoError {
 			Error::Undefined(_) => IoError::from(ErrorKind::Other),
 			Error::UndefinedModulationType => IoError::from(ErrorKind::InvalidInput),
 			Error::NoDeviceFound => IoError::from(ErrorKind::NotFound),
+			Error::LibraryNotFound => IoError::from(ErrorKind::NotFound),
+			Error::SymbolNotFound => IoError::from(ErrorKind::NotFound),
+			Error::UnsupportedLibraryVersion => IoError::from(ErrorKind::Unsupported),
+			Error::LibraryAlreadyLoaded => IoError::from(ErrorKind::AlreadyExists),
+			Error::InvalidCrc => IoError::from(ErrorKind::InvalidData),
 
 			// libnfc errors
 			Error::Io => IoError::from(ErrorKind::Other),
@@ -105,6 +156,11 @@ impl std::fmt::Display for Error {
 			Error::Undefined(errno) => write!(f, "Unknown libnfc error: {}", errno),
 			Error::UndefinedModulationType => write!(f, "Undefined modulation type"),
 			Error::NoDeviceFound => write!(f, "No device found"),
+			Error::LibraryNotFound => write!(f, "Unable to load libnfc"),
+			Error::SymbolNotFound => write!(f, "Missing symbol in libnfc"),
+			Error::UnsupportedLibraryVersion => write!(f, "Unsupported libnfc version"),
+			Error::LibraryAlreadyLoaded => write!(f, "libnfc is already loaded"),
+			Error::InvalidCrc => write!(f, "Invalid CRC in response"),
 
 			// libnfc errors
 			Error::Io => write!(f, "Input/output error, device may not be usable anymore without re-opening it"),
@@ -128,6 +184,27 @@ impl std::error::Error for Error {}
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// Error returned by the parsers of protocol structures, such as `Ats`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseError {
+	/// The data ends before the length indicated by its earlier bytes
+	Truncated { expected: usize, len: usize },
+	/// A byte has a value which is not allowed at its offset
+	InvalidValue { offset: usize, value: u8 },
+}
+
+impl std::fmt::Display for ParseError {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			ParseError::Truncated{ expected, len } => write!(f, "Truncated: expected {} bytes, got {}", expected, len),
+			ParseError::InvalidValue{ offset, value } => write!(f, "Invalid value {:02x} at offset {}", value, offset),
+		}
+	}
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(feature = "libnfc")]
 pub(crate) fn wrap_err(res: c_int) -> Result<()> {
 	if res < 0 {
 		return Err(res.into());
@@ -135,6 +212,7 @@ pub(crate) fn wrap_err(res: c_int) -> Result<()> {
 	Ok(())
 }
 
+#[cfg(feature = "libnfc")]
 pub(crate) fn wrap_err_usize(res: c_int) -> Result<usize> {
 	if res < 0 {
 		return Err(res.into());
@@ -144,6 +222,7 @@ pub(crate) fn wrap_err_usize(res: c_int) -> Result<usize> {
 
 /// Safe version of the int values used for timeouts in libnfc
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Timeout {
 	None,
 	Default,
@@ -175,11 +254,13 @@ impl From<c_int> for Timeout {
 
 /// Safe version of nfc_mode
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Mode {
 	Target,
 	Initiator,
 }
 
+#[cfg(feature = "libnfc")]
 impl From<Mode> for nfc1_sys::nfc_mode {
 	fn from(input: Mode) -> nfc1_sys::nfc_mode {
 		match input {
@@ -191,6 +272,7 @@ impl From<Mode> for nfc1_sys::nfc_mode {
 
 /// Safe version of nfc_baud_rate
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BaudRate {
 	Baud106,
 	Baud212,
@@ -199,13 +281,20 @@ pub enum BaudRate {
 	Undefined,
 }
 
-impl ToString for BaudRate {
-	fn to_string(&self) -> String {
-		let baud_rate: nfc1_sys::nfc_baud_rate = self.clone().into();
-		return unsafe { CStr::from_ptr(str_nfc_baud_rate(baud_rate)) }.to_string_lossy().into_owned();
+// Same strings as str_nfc_baud_rate
+impl std::fmt::Display for BaudRate {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			BaudRate::Baud106 => write!(f, "106 kbps"),
+			BaudRate::Baud212 => write!(f, "212 kbps"),
+			BaudRate::Baud424 => write!(f, "424 kbps"),
+			BaudRate::Baud847 => write!(f, "847 kbps"),
+			BaudRate::Undefined => write!(f, "undefined baud rate"),
+		}
 	}
 }
 
+#[cfg(feature = "libnfc")]
 impl From<BaudRate> for nfc1_sys::nfc_baud_rate {
 	fn from(input: BaudRate) -> nfc1_sys::nfc_baud_rate {
 		match input {
@@ -218,6 +307,7 @@ impl From<BaudRate> for nfc1_sys::nfc_baud_rate {
 	}
 }
 
+#[cfg(feature = "libnfc")]
 impl From<nfc1_sys::nfc_baud_rate> for BaudRate {
 	fn from(input: nfc1_sys::nfc_baud_rate) -> Self {
 		match input {
@@ -232,6 +322,7 @@ impl From<nfc1_sys::nfc_baud_rate> for BaudRate {
 
 /// Safe version of nfc_property
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Property {
 	TimeoutCommand,
 	TimeoutAtr,
@@ -250,6 +341,7 @@ pub enum Property {
 	ForceSpeed106,
 }
 
+#[cfg(feature = "libnfc")]
 impl From<Property> for nfc1_sys::nfc_property {
 	fn from(input: Property) -> nfc1_sys::nfc_property {
 		match input {
@@ -273,7 +365,8 @@ impl From<Property> for nfc1_sys::nfc_property {
 }
 
 /// Safe version of nfc_modulation_type
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ModulationType {
 	Iso14443a,
 	Jewel,
@@ -288,13 +381,26 @@ pub enum ModulationType {
 	Undefined,
 }
 
-impl ToString for ModulationType {
-	fn to_string(&self) -> String {
-		let modulation_type: nfc1_sys::nfc_modulation_type = self.clone().into();
-		return unsafe { CStr::from_ptr(str_nfc_modulation_type(modulation_type)) }.to_string_lossy().into_owned();
+// Same strings as str_nfc_modulation_type
+impl std::fmt::Display for ModulationType {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			ModulationType::Iso14443a => write!(f, "ISO/IEC 14443A"),
+			ModulationType::Jewel => write!(f, "Innovision Jewel"),
+			ModulationType::Iso14443b => write!(f, "ISO/IEC 14443-4B"),
+			ModulationType::Iso14443bi => write!(f, "ISO/IEC 14443-4B'"),
+			ModulationType::Iso14443b2sr => write!(f, "ISO/IEC 14443-2B ST SRx"),
+			ModulationType::Iso14443b2ct => write!(f, "ISO/IEC 14443-2B ASK CTx"),
+			ModulationType::Felica => write!(f, "FeliCa"),
+			ModulationType::Dep => write!(f, "D.E.P."),
+			ModulationType::Barcode => write!(f, "Thinfilm NFC Barcode"),
+			ModulationType::Iso14443biClass => write!(f, "ISO/IEC 14443-2B-3B iClass (Picopass)"),
+			ModulationType::Undefined => write!(f, "???"),
+		}
 	}
 }
 
+#[cfg(feature = "libnfc")]
 impl From<ModulationType> for nfc1_sys::nfc_modulation_type {
 	fn from(input: ModulationType) -> nfc1_sys::nfc_modulation_type {
 		match input {
@@ -313,6 +419,7 @@ impl From<ModulationType> for nfc1_sys::nfc_modulation_type {
 	}
 }
 
+#[cfg(feature = "libnfc")]
 impl From<nfc1_sys::nfc_modulation_type> for ModulationType {
 	fn from(input: nfc1_sys::nfc_modulation_type) -> Self {
 		match input {
@@ -333,11 +440,19 @@ impl From<nfc1_sys::nfc_modulation_type> for ModulationType {
 
 /// Safe version of nfc_modulation
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Modulation {
 	pub modulation_type: ModulationType,
 	pub baud_rate: BaudRate,
 }
 
+impl std::fmt::Display for Modulation {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{} ({})", self.modulation_type, self.baud_rate)
+	}
+}
+
+#[cfg(feature = "libnfc")]
 impl From<&Modulation> for nfc1_sys::nfc_modulation {
 	fn from(input: &Modulation) -> nfc1_sys::nfc_modulation {
 		nfc1_sys::nfc_modulation {
@@ -347,6 +462,7 @@ impl From<&Modulation> for nfc1_sys::nfc_modulation {
 	}
 }
 
+#[cfg(feature = "libnfc")]
 impl From<nfc1_sys::nfc_modulation> for Modulation {
 	fn from(input: nfc1_sys::nfc_modulation) -> Self {
 		Self{
@@ -358,12 +474,14 @@ impl From<nfc1_sys::nfc_modulation> for Modulation {
 
 /// Safe version of nfc_dep_mode
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DepMode {
 	Undefined,
 	Passive,
 	Active,
 }
 
+#[cfg(feature = "libnfc")]
 impl From<DepMode> for nfc1_sys::nfc_baud_rate {
 	fn from(input: DepMode) -> nfc1_sys::nfc_baud_rate {
 		match input {
@@ -374,6 +492,7 @@ impl From<DepMode> for nfc1_sys::nfc_baud_rate {
 	}
 }
 
+#[cfg(feature = "libnfc")]
 impl From<nfc1_sys::nfc_baud_rate> for DepMode {
 	fn from(input: nfc1_sys::nfc_baud_rate) -> DepMode {
 		match input {
@@ -387,27 +506,23 @@ impl From<nfc1_sys::nfc_baud_rate> for DepMode {
 // Misc. functions
 
 pub fn iso14443a_crc(data: &mut [u8]) -> Vec<u8> {
-	let mut crc = vec![0u8; 2];
-	unsafe { nfc_iso14443a_crc(data.as_mut_ptr(), data.len(), crc.as_mut_ptr()) };
-	crc
+	crc::crc_a(data).to_vec()
 }
 
 pub fn iso14443a_crc_append(data: &mut Vec<u8>) {
-	unsafe { nfc_iso14443a_crc_append(data.as_mut_ptr(), data.len()) }
+	crc::append_crc_a(data);
 }
 
 pub fn iso14443b_crc(data: &mut [u8]) -> Vec<u8> {
-	let mut crc = vec![0u8; 2];
-	unsafe { nfc_iso14443b_crc(data.as_mut_ptr(), data.len(), crc.as_mut_ptr()) };
-	crc
+	crc::crc_b(data).to_vec()
 }
 
 pub fn iso14443b_crc_append(data: &mut Vec<u8>) {
-	unsafe { nfc_iso14443b_crc_append(data.as_mut_ptr(), data.len()) }
+	crc::append_crc_b(data);
 }
 
 pub fn iso14443a_locate_historical_bytes(ats: &[u8]) -> Option<&[u8]> {
-	if ats.len() != 0 {
+	if !ats.is_empty() {
 		let mut offset = 1;
 		if ats[0] & 0x10 != 0 { // TA
 			offset += 1;
@@ -426,7 +541,36 @@ pub fn iso14443a_locate_historical_bytes(ats: &[u8]) -> Option<&[u8]> {
 	None
 }
 
-pub fn version() -> &'static str {
+/// Loads libnfc from the default library search path, if it is not already loaded.
+///
+/// This is done implicitly by `Context::new`, `try_version` and the other entry points.
+#[cfg(feature = "dlopen")]
+pub fn load_library() -> Result<()> {
+	ffi::load(None)
+}
+
+/// Loads libnfc from the first of the given paths which can be opened.
+///
+/// Returns `Error::LibraryAlreadyLoaded` if libnfc was already loaded, or an attempt
+/// was made, for example implicitly by `Context::new`. Call this first.
+#[cfg(feature = "dlopen")]
+pub fn load_library_from<P: AsRef<OsStr>>(paths: &[P]) -> Result<()> {
+	let paths: Vec<&OsStr> = paths.iter().map(|path| path.as_ref()).collect();
+	ffi::load(Some(&paths))
+}
+
+/// Returns the libnfc version, loading libnfc first with the `dlopen` feature
+#[cfg(feature = "libnfc")]
+pub fn try_version() -> Result<&'static str> {
+	#[cfg(feature = "dlopen")]
+	load_library()?;
 	// XXX: Safe because nfc_version returns a constant string (#define)
-	unsafe { CStr::from_ptr(nfc_version()) }.to_str().unwrap()
+	Ok(unsafe { CStr::from_ptr(nfc_version()) }.to_str().unwrap())
+}
+
+/// Returns the libnfc version. With the `dlopen` feature, an empty string is returned
+/// if libnfc can not be loaded, see `try_version`.
+#[cfg(feature = "libnfc")]
+pub fn version() -> &'static str {
+	try_version().unwrap_or_default()
 }
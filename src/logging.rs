@@ -0,0 +1,240 @@
+use log::{Level, LevelFilter};
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::raw::c_int;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+use std::sync::RwLock;
+use std::thread::{self, JoinHandle};
+
+const LOG_LEVEL_ENV: &str = "LIBNFC_LOG_LEVEL";
+const STDERR_FILENO: c_int = 2;
+
+/// Safe version of the NFC_LOG_PRIORITY_* constants
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum LogPriority {
+	None = 0,
+	Error = 1,
+	Info = 2,
+	Debug = 3,
+}
+
+/// Safe version of the NFC_LOG_GROUP_* constants
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum LogGroup {
+	General = 1,
+	Config = 2,
+	Chip = 3,
+	Driver = 4,
+	Com = 5,
+	Libusb = 6,
+}
+
+impl From<u32> for LogPriority {
+	fn from(input: u32) -> Self {
+		match input & 0x03 {
+			1 => LogPriority::Error,
+			2 => LogPriority::Info,
+			3 => LogPriority::Debug,
+			_ => LogPriority::None,
+		}
+	}
+}
+
+impl From<LogPriority> for Level {
+	fn from(input: LogPriority) -> Self {
+		match input {
+			LogPriority::None | LogPriority::Error => Level::Error,
+			LogPriority::Info => Level::Info,
+			LogPriority::Debug => Level::Debug,
+		}
+	}
+}
+
+/// libnfc log level bitmask, as used by `log_level` in `libnfc.conf` and `LIBNFC_LOG_LEVEL`.
+///
+/// Bits 0-1 hold the global priority, and bits `group * 2` to `group * 2 + 1` hold the
+/// priority of each group. A message is logged if its priority is at most the global
+/// priority or the priority of its group.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct LogLevel(pub u32);
+
+impl LogLevel {
+	pub fn new(global: LogPriority) -> Self {
+		Self(global as u32)
+	}
+
+	pub fn with_group(self, group: LogGroup, priority: LogPriority) -> Self {
+		let shift = group as u32 * 2;
+		Self((self.0 & !(0x03 << shift)) | ((priority as u32) << shift))
+	}
+
+	pub fn global(&self) -> LogPriority {
+		self.0.into()
+	}
+
+	pub fn group(&self, group: LogGroup) -> LogPriority {
+		(self.0 >> (group as u32 * 2)).into()
+	}
+}
+
+impl Default for LogLevel {
+	fn default() -> Self {
+		Self::new(LogPriority::Error)
+	}
+}
+
+impl From<u32> for LogLevel {
+	fn from(input: u32) -> Self {
+		Self(input)
+	}
+}
+
+impl From<LogLevel> for u32 {
+	fn from(input: LogLevel) -> Self {
+		input.0
+	}
+}
+
+/// Returns the log level libnfc currently uses to decide which messages to emit
+pub fn log_level() -> LogLevel {
+	env::var(LOG_LEVEL_ENV).ok()
+		.and_then(|level| level.trim().parse().ok())
+		.map(LogLevel)
+		.unwrap_or_default()
+}
+
+/// Changes which messages libnfc emits by setting `LIBNFC_LOG_LEVEL`.
+///
+/// libnfc reads `LIBNFC_LOG_LEVEL` every time it logs a message. Note that `Context::new`
+/// resets it to the `log_level` of the new context, which is read from the same
+/// environment variable unless overridden by `libnfc.conf` (or `Config::log_level`).
+///
+/// # Safety
+///
+/// This modifies the process environment, which libnfc reads with `getenv` without any
+/// locking. It must be called at startup, before any other thread is spawned and before
+/// any `Context` is created.
+pub unsafe fn set_log_level(level: LogLevel) {
+	env::set_var(LOG_LEVEL_ENV, level.0.to_string());
+}
+
+static CATEGORY_LEVELS: RwLock<Vec<(String, LevelFilter)>> = RwLock::new(Vec::new());
+
+/// Limits which captured messages are re-emitted for a category (e.g. `libnfc.chip`) and all of its sub-categories.
+///
+/// The most specific matching category wins. Messages of categories without a level
+/// set are passed on to the `log` crate as-is.
+pub fn set_category_level(category: &str, level: LevelFilter) {
+	let mut category_levels = CATEGORY_LEVELS.write().unwrap_or_else(|err| err.into_inner());
+	category_levels.retain(|(existing, _)| existing != category);
+	category_levels.push((category.to_string(), level));
+}
+
+fn category_level(category: &str) -> LevelFilter {
+	let category_levels = CATEGORY_LEVELS.read().unwrap_or_else(|err| err.into_inner());
+	category_levels.iter()
+		.filter(|(prefix, _)| category == prefix || (category.starts_with(prefix.as_str()) && category[prefix.len()..].starts_with('.')))
+		.max_by_key(|(prefix, _)| prefix.len())
+		.map_or(LevelFilter::Trace, |(_, level)| *level)
+}
+
+// libnfc writes each message as "<priority>\t<category>\t<message>\n" (see log_put in libnfc/log.c)
+pub(crate) fn parse_line(line: &str) -> Option<(Level, &str, &str)> {
+	let mut parts = line.splitn(3, '\t');
+	let level = match parts.next()? {
+		"error" => Level::Error,
+		"info" => Level::Info,
+		"debug" => Level::Debug,
+		"unknown" => Level::Trace,
+		_ => return None,
+	};
+	let category = parts.next().filter(|category| !category.is_empty() && !category.contains(' '))?;
+	let message = parts.next()?;
+	Some((level, category, message))
+}
+
+fn forward(input: File, mut stderr: File) {
+	let mut previous_was_log = false;
+	for line in BufReader::new(input).split(b'\n') {
+		let Ok(line) = line else { break };
+		let text = String::from_utf8_lossy(&line);
+		if let Some((level, category, message)) = parse_line(&text) {
+			if level <= category_level(category) {
+				log::log!(target: category, level, "{}", message.trim_end());
+			}
+			previous_was_log = true;
+			continue;
+		}
+		// libnfc messages ending with a newline are followed by an empty line
+		if previous_was_log && line.is_empty() {
+			previous_was_log = false;
+			continue;
+		}
+		previous_was_log = false;
+		let _ = stderr.write_all(&line).and_then(|_| stderr.write_all(b"\n"));
+	}
+}
+
+fn check(res: c_int) -> io::Result<c_int> {
+	if res < 0 {
+		return Err(io::Error::last_os_error());
+	}
+	Ok(res)
+}
+
+fn owned(fd: c_int) -> io::Result<OwnedFd> {
+	check(fd).map(|fd| unsafe { OwnedFd::from_raw_fd(fd) })
+}
+
+/// Captures libnfc log output and re-emits it through the `log` crate until dropped.
+///
+/// libnfc has no logging callback and always writes to `stderr`, so this redirects the
+/// process-wide `stderr` file descriptor into a pipe. Lines which are not libnfc log
+/// messages are passed through to the original `stderr` unchanged.
+///
+/// Output is forwarded by a background thread. If the process exits without dropping
+/// the capture (e.g. through `std::process::exit` or an aborting panic), output still
+/// in the pipe, including panic messages, can be lost.
+pub struct LogCapture {
+	original_stderr: OwnedFd,
+	thread: Option<JoinHandle<()>>,
+}
+
+impl LogCapture {
+	pub fn new() -> io::Result<Self> {
+		let mut fds = [0 as c_int; 2];
+		check(unsafe { libc::pipe(fds.as_mut_ptr()) })?;
+		let (read_fd, write_fd) = unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) };
+		let original_stderr = owned(unsafe { libc::dup(STDERR_FILENO) })?;
+		let forward_stderr = original_stderr.try_clone()?;
+
+		let input = File::from(read_fd);
+		let stderr = File::from(forward_stderr);
+		let thread = thread::Builder::new()
+			.name("nfc1-log".to_string())
+			.spawn(move || forward(input, stderr))?;
+		// If this fails, dropping write_fd ends the thread
+		check(unsafe { libc::dup2(write_fd.as_raw_fd(), STDERR_FILENO) })?;
+		Ok(Self{ original_stderr, thread: Some(thread) })
+	}
+}
+
+/// Restores the original `stderr` and waits for the forwarding thread to pass on the
+/// remaining output. The thread exits once every copy of the pipe is closed, so this
+/// blocks while child processes which inherited it are still running.
+impl Drop for LogCapture {
+	fn drop(&mut self) {
+		unsafe {
+			libc::dup2(self.original_stderr.as_raw_fd(), STDERR_FILENO);
+		}
+		if let Some(thread) = self.thread.take() {
+			let _ = thread.join();
+		}
+	}
+}
+
+/// Starts capturing libnfc log output, see `LogCapture`
+pub fn capture() -> io::Result<LogCapture> {
+	LogCapture::new()
+}
@@ -0,0 +1,123 @@
+//! Operational metrics of `Device` operations, recorded through the [`metrics`](https://docs.rs/metrics) facade.
+//!
+//! Every metric is labeled with the `connstring` of the device, so that readers
+//! can be told apart. Install any `metrics` recorder to collect them, or use
+//! `install_prometheus_recorder`/`install_prometheus_endpoint` with the
+//! `prometheus` feature.
+
+use crate::{Error, Modulation};
+use ::metrics::{Unit, counter, describe_counter, describe_histogram, histogram};
+use std::time::Duration;
+use std::sync::Arc;
+
+/// Counter of all device operations, labeled with `operation` and `connstring`
+pub const OPERATIONS_TOTAL: &str = "nfc1_operations_total";
+/// Counter of target selections (select, list and poll operations), labeled with `connstring` and `modulation`
+pub const SELECTS_TOTAL: &str = "nfc1_selects_total";
+/// Counter of transceive operations, labeled with `operation` and `connstring`
+pub const TRANSCEIVES_TOTAL: &str = "nfc1_transceives_total";
+/// Counter of bytes sent to targets, labeled with `connstring`
+pub const TX_BYTES_TOTAL: &str = "nfc1_tx_bytes_total";
+/// Counter of bytes received from targets, labeled with `connstring`
+pub const RX_BYTES_TOTAL: &str = "nfc1_rx_bytes_total";
+/// Counter of failed operations, labeled with `operation`, `connstring` and `error` (the `Error` variant)
+pub const ERRORS_TOTAL: &str = "nfc1_errors_total";
+/// Histogram of operation latency, labeled with `operation` and `connstring`
+pub const OPERATION_DURATION_SECONDS: &str = "nfc1_operation_duration_seconds";
+
+/// Histogram buckets used for `OPERATION_DURATION_SECONDS` by the Prometheus helpers
+pub const DURATION_BUCKETS: &[f64] = &[0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+/// Registers descriptions and units of all metrics with the installed recorder
+pub fn describe() {
+	describe_counter!(OPERATIONS_TOTAL, Unit::Count, "Device operations");
+	describe_counter!(SELECTS_TOTAL, Unit::Count, "Target selections");
+	describe_counter!(TRANSCEIVES_TOTAL, Unit::Count, "Transceive operations");
+	describe_counter!(TX_BYTES_TOTAL, Unit::Bytes, "Bytes sent to targets");
+	describe_counter!(RX_BYTES_TOTAL, Unit::Bytes, "Bytes received from targets");
+	describe_counter!(ERRORS_TOTAL, Unit::Count, "Failed device operations");
+	describe_histogram!(OPERATION_DURATION_SECONDS, Unit::Seconds, "Device operation latency");
+}
+
+fn error_name(err: &Error) -> &'static str {
+	match err {
+		Error::Malloc => "Malloc",
+		Error::Undefined(_) => "Undefined",
+		Error::UndefinedModulationType => "UndefinedModulationType",
+		Error::NoDeviceFound => "NoDeviceFound",
+		Error::LibraryNotFound => "LibraryNotFound",
+		Error::SymbolNotFound => "SymbolNotFound",
+		Error::UnsupportedLibraryVersion => "UnsupportedLibraryVersion",
+		Error::LibraryAlreadyLoaded => "LibraryAlreadyLoaded",
+		Error::InvalidCrc => "InvalidCrc",
+		Error::Io => "Io",
+		Error::InvalidArgument => "InvalidArgument",
+		Error::DeviceNotSupported => "DeviceNotSupported",
+		Error::NoSuchDeviceFound => "NoSuchDeviceFound",
+		Error::BufferOverflow => "BufferOverflow",
+		Error::Timeout => "Timeout",
+		Error::OperationAborted => "OperationAborted",
+		Error::NotImplemented => "NotImplemented",
+		Error::TargetReleased => "TargetReleased",
+		Error::RfTransmissionError => "RfTransmissionError",
+		Error::MifareAuthFailed => "MifareAuthFailed",
+		Error::Soft => "Soft",
+		Error::Chip => "Chip",
+	}
+}
+
+fn is_select(operation: &str) -> bool {
+	operation.starts_with("initiator_select_") || operation.starts_with("initiator_poll_") || operation == "initiator_list_passive_targets"
+}
+
+pub(crate) fn record_tx(connstring: &Arc<str>, len: usize) {
+	counter!(TX_BYTES_TOTAL, "connstring" => connstring.clone()).increment(len as u64);
+}
+
+pub(crate) fn record_rx(connstring: &Arc<str>, len: usize) {
+	counter!(RX_BYTES_TOTAL, "connstring" => connstring.clone()).increment(len as u64);
+}
+
+pub(crate) fn record_error(operation: &'static str, connstring: &Arc<str>, err: &Error) {
+	counter!(ERRORS_TOTAL, "operation" => operation, "connstring" => connstring.clone(), "error" => error_name(err)).increment(1);
+}
+
+pub(crate) fn record_operation(operation: &'static str, connstring: &Arc<str>, modulation: Option<&Modulation>, duration: Duration) {
+	counter!(OPERATIONS_TOTAL, "operation" => operation, "connstring" => connstring.clone()).increment(1);
+	histogram!(OPERATION_DURATION_SECONDS, "operation" => operation, "connstring" => connstring.clone()).record(duration.as_secs_f64());
+	if is_select(operation) {
+		let modulation = modulation.map(|modulation| modulation.to_string()).unwrap_or_default();
+		counter!(SELECTS_TOTAL, "connstring" => connstring.clone(), "modulation" => modulation).increment(1);
+	} else if operation.contains("transceive") {
+		counter!(TRANSCEIVES_TOTAL, "operation" => operation, "connstring" => connstring.clone()).increment(1);
+	}
+}
+
+#[cfg(feature = "prometheus")]
+pub use metrics_exporter_prometheus::{BuildError, PrometheusHandle};
+
+#[cfg(feature = "prometheus")]
+fn prometheus_builder() -> Result<metrics_exporter_prometheus::PrometheusBuilder, BuildError> {
+	use metrics_exporter_prometheus::{Matcher, PrometheusBuilder};
+	PrometheusBuilder::new().set_buckets_for_metric(Matcher::Full(OPERATION_DURATION_SECONDS.to_string()), DURATION_BUCKETS)
+}
+
+/// Installs a Prometheus recorder as the global `metrics` recorder.
+///
+/// `PrometheusHandle::render` returns the Prometheus text format, to be served by your own HTTP server.
+#[cfg(feature = "prometheus")]
+pub fn install_prometheus_recorder() -> Result<PrometheusHandle, BuildError> {
+	let handle = prometheus_builder()?.install_recorder()?;
+	describe();
+	Ok(handle)
+}
+
+/// Installs a Prometheus recorder as the global `metrics` recorder, and serves the Prometheus text format over HTTP on `addr`.
+///
+/// The HTTP listener runs on the current Tokio runtime if there is one, or in a background thread otherwise.
+#[cfg(feature = "prometheus")]
+pub fn install_prometheus_endpoint(addr: std::net::SocketAddr) -> Result<(), BuildError> {
+	prometheus_builder()?.with_http_listener(addr).install()?;
+	describe();
+	Ok(())
+}
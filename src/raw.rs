@@ -0,0 +1,232 @@
+//! Bit-level ISO/IEC 14443A frames, for talking to targets below the level of `libnfc`.
+//!
+//! A `RawFrame` holds the bytes, bit count and parity bits expected by
+//! `Device::initiator_transceive_bits` and its `_with_parity` variants. A
+//! `RawSession` configures the device for raw frames and sends them.
+
+use crate::crc;
+use crate::{Error, Result};
+#[cfg(feature = "libnfc")]
+use crate::{Device, Property};
+
+/// ISO/IEC 14443A anticollision cascade level, identified by its SEL code
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+pub enum CascadeLevel {
+	One,
+	Two,
+	Three,
+}
+
+impl CascadeLevel {
+	pub fn sel(&self) -> u8 {
+		match self {
+			Self::One => 0x93,
+			Self::Two => 0x95,
+			Self::Three => 0x97,
+		}
+	}
+
+	pub fn from_sel(sel: u8) -> Option<Self> {
+		match sel {
+			0x93 => Some(Self::One),
+			0x95 => Some(Self::Two),
+			0x97 => Some(Self::Three),
+			_ => None,
+		}
+	}
+
+	/// Returns the next cascade level, if any
+	pub fn next(&self) -> Option<Self> {
+		match self {
+			Self::One => Some(Self::Two),
+			Self::Two => Some(Self::Three),
+			Self::Three => None,
+		}
+	}
+}
+
+/// An ISO/IEC 14443A frame with its bit count and parity bits
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RawFrame {
+	data: Vec<u8>,
+	bits: usize,
+	parity: Vec<u8>,
+}
+
+impl RawFrame {
+	/// Creates a frame of the first `bits` bits of `data`, without CRC
+	pub fn from_bits(data: &[u8], bits: usize) -> Result<Self> {
+		if bits == 0 || bits > data.len() * 8 {
+			return Err(Error::InvalidArgument);
+		}
+		let mut data = data[..bits.div_ceil(8)].to_vec();
+		if bits & 7 != 0 {
+			*data.last_mut().unwrap() &= (1 << (bits % 8)) - 1;
+		}
+		let parity = crc::parity(&data);
+		Ok(Self{ data, bits, parity })
+	}
+
+	/// Creates a short frame of 7 bits, such as REQA (0x26) or WUPA (0x52)
+	pub fn short(command: u8) -> Self {
+		let data = vec![command & 0x7f];
+		let parity = crc::parity(&data);
+		Self{ data, bits: 7, parity }
+	}
+
+	/// Creates a standard frame, appending the CRC_A
+	pub fn standard(data: &[u8]) -> Self {
+		let mut data = data.to_vec();
+		crc::append_crc_a(&mut data);
+		Self::without_crc(&data)
+	}
+
+	/// Creates a standard frame without CRC_A, such as ANTICOLLISION
+	pub fn without_crc(data: &[u8]) -> Self {
+		let parity = crc::parity(data);
+		Self{ data: data.to_vec(), bits: data.len() * 8, parity }
+	}
+
+	/// Creates a bit oriented anticollision frame with the first `known_bits` bits of the UID CLn in `uid`.
+	///
+	/// With no known bits, this is the ANTICOLLISION command (`93 20` at cascade
+	/// level 1). Use `select` once all 32 bits are known.
+	pub fn anticollision(level: CascadeLevel, uid: &[u8], known_bits: usize) -> Result<Self> {
+		if known_bits >= 32 || known_bits > uid.len() * 8 {
+			return Err(Error::InvalidArgument);
+		}
+		// NVB: number of valid bytes in the upper nibble, remaining bits in the lower nibble
+		let nvb = (((2 + known_bits / 8) << 4) | (known_bits % 8)) as u8;
+		let mut data = vec![level.sel(), nvb];
+		data.extend_from_slice(&uid[..known_bits.div_ceil(8)]);
+		Self::from_bits(&data, 16 + known_bits)
+	}
+
+	/// Creates the SELECT command for the complete UID CLn, with its BCC and CRC_A
+	pub fn select(level: CascadeLevel, uid: &[u8; 4]) -> Self {
+		let bcc = uid.iter().fold(0, |bcc, byte| bcc ^ byte);
+		Self::standard(&[level.sel(), 0x70, uid[0], uid[1], uid[2], uid[3], bcc])
+	}
+
+	pub fn data(&self) -> &[u8] {
+		&self.data
+	}
+
+	/// Returns the number of bits to send, excluding parity bits
+	pub fn bits(&self) -> usize {
+		self.bits
+	}
+
+	/// Returns one odd parity bit per byte, for `_with_parity` methods
+	pub fn parity(&self) -> &[u8] {
+		&self.parity
+	}
+}
+
+/// A frame received in reply to a `RawFrame`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct RawResponse {
+	pub data: Vec<u8>,
+	/// Number of bits received, excluding parity bits
+	pub bits: usize,
+	/// Received parity bits, when parity is handled by the caller
+	pub parity: Option<Vec<u8>>,
+}
+
+impl RawResponse {
+	/// Returns whether the response ends with a valid CRC_A
+	pub fn crc_valid(&self) -> bool {
+		self.bits & 7 == 0 && crc::check_crc_a(&self.data)
+	}
+
+	/// Returns the response without its CRC_A, or `None` if the CRC_A is not valid
+	pub fn payload(&self) -> Option<&[u8]> {
+		if self.bits & 7 != 0 {
+			return None;
+		}
+		crc::strip_crc_a(&self.data)
+	}
+
+	/// Returns whether the received parity bits are correct, or `true` if parity was handled by the device
+	pub fn parity_valid(&self) -> bool {
+		match &self.parity {
+			Some(parity) => crc::check_parity(&self.data[..self.bits / 8], parity),
+			None => true,
+		}
+	}
+}
+
+/// A device configured to send `RawFrame`s as initiator.
+///
+/// Creating a session disables CRC handling, easy framing and automatic
+/// ISO/IEC 14443-4 switching, so that frames are sent exactly as built.
+/// Dropping it restores the defaults set by `Device::initiator_init`.
+#[cfg(feature = "libnfc")]
+pub struct RawSession<'a> {
+	device: &'a mut Device,
+	handle_parity: bool,
+}
+
+#[cfg(feature = "libnfc")]
+impl<'a> RawSession<'a> {
+	/// Starts a session in which parity bits are generated and checked by the device
+	pub fn new(device: &'a mut Device) -> Result<Self> {
+		Self::configure(device, true)
+	}
+
+	/// Starts a session in which parity bits are taken from the frames, and returned in responses
+	pub fn with_manual_parity(device: &'a mut Device) -> Result<Self> {
+		Self::configure(device, false)
+	}
+
+	fn configure(device: &'a mut Device, handle_parity: bool) -> Result<Self> {
+		device.set_property_bool(Property::HandleCrc, false)?;
+		device.set_property_bool(Property::HandleParity, handle_parity)?;
+		device.set_property_bool(Property::EasyFraming, false)?;
+		device.set_property_bool(Property::AutoIso144434, false)?;
+		Ok(Self{ device, handle_parity })
+	}
+
+	pub fn device(&mut self) -> &mut Device {
+		self.device
+	}
+
+	pub(crate) fn handles_parity(&self) -> bool {
+		self.handle_parity
+	}
+
+	/// Sends a frame and returns the response, whose CRC is not checked
+	pub fn transceive(&mut self, frame: &RawFrame, rx_len: usize) -> Result<RawResponse> {
+		let (mut data, bits, mut parity) = if self.handle_parity {
+			let data = self.device.initiator_transceive_bits(frame.data(), frame.bits(), rx_len)?;
+			let bits = data.len();
+			(data, bits, None)
+		} else {
+			let (data, parity) = self.device.initiator_transceive_bits_with_parity(frame.data(), frame.bits(), frame.parity(), rx_len)?;
+			let bits = data.len();
+			(data, bits, Some(parity))
+		};
+		// The device methods size their buffers by the number of bits received
+		data.truncate(bits.div_ceil(8));
+		if let Some(parity) = &mut parity {
+			parity.truncate(bits / 8);
+		}
+		Ok(RawResponse{ data, bits, parity })
+	}
+
+	/// Sends a frame and returns the response without its CRC_A, or `Error::InvalidCrc` if it is not valid
+	pub fn transceive_with_crc(&mut self, frame: &RawFrame, rx_len: usize) -> Result<Vec<u8>> {
+		let response = self.transceive(frame, rx_len)?;
+		response.payload().map(|payload| payload.to_vec()).ok_or(Error::InvalidCrc)
+	}
+}
+
+#[cfg(feature = "libnfc")]
+impl Drop for RawSession<'_> {
+	fn drop(&mut self) {
+		let _ = self.device.set_property_bool(Property::HandleCrc, true);
+		let _ = self.device.set_property_bool(Property::HandleParity, true);
+		let _ = self.device.set_property_bool(Property::EasyFraming, true);
+		let _ = self.device.set_property_bool(Property::AutoIso144434, true);
+	}
+}
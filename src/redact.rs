@@ -0,0 +1,150 @@
+//! Redaction of identifiers and payloads in `Debug`/`Display` output and frame tracing.
+//!
+//! UIDs, ATS, general bytes and other data which can identify a card or its
+//! holder are formatted through `Redacted`, according to the current
+//! `Redaction` policy. The policy defaults to `Redaction::Mask`; full data is
+//! only shown after explicitly opting in with `set_policy(Redaction::Reveal)`
+//! or `reveal`.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::RwLock;
+
+/// How sensitive bytes are formatted
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub enum Redaction {
+	/// Show all bytes
+	Reveal,
+	/// Replace every byte with `**`, only revealing the length
+	#[default]
+	Mask,
+	/// Show the given number of leading bytes and mask the rest
+	Truncate(usize),
+	/// Replace the bytes with a salted SipHash-2-4 digest, so that equal values can still be correlated
+	Hash(Vec<u8>),
+}
+
+static POLICY: RwLock<Redaction> = RwLock::new(Redaction::Mask);
+
+thread_local! {
+	static POLICY_OVERRIDE: RefCell<Option<Redaction>> = const { RefCell::new(None) };
+}
+
+/// Returns the redaction policy in effect on the current thread
+pub fn policy() -> Redaction {
+	POLICY_OVERRIDE.with(|policy| policy.borrow().clone())
+		.unwrap_or_else(|| POLICY.read().unwrap_or_else(|err| err.into_inner()).clone())
+}
+
+/// Sets the process-wide redaction policy
+pub fn set_policy(policy: Redaction) {
+	*POLICY.write().unwrap_or_else(|err| err.into_inner()) = policy;
+}
+
+/// Runs `f` with `policy` in effect on the current thread, regardless of the process-wide policy
+pub fn with_policy<R>(policy: Redaction, f: impl FnOnce() -> R) -> R {
+	struct Restore(Option<Redaction>);
+	impl Drop for Restore {
+		fn drop(&mut self) {
+			let previous = self.0.take();
+			POLICY_OVERRIDE.with(|policy| *policy.borrow_mut() = previous);
+		}
+	}
+
+	let _restore = Restore(POLICY_OVERRIDE.with(|current| current.borrow_mut().replace(policy)));
+	f()
+}
+
+/// Runs `f` with full data revealed on the current thread
+pub fn reveal<R>(f: impl FnOnce() -> R) -> R {
+	with_policy(Redaction::Reveal, f)
+}
+
+/// Formats sensitive bytes as space-separated hex, according to the current `Redaction` policy
+#[derive(Clone, Copy)]
+pub struct Redacted<'a>(pub &'a [u8]);
+
+impl Redacted<'_> {
+	fn fmt_with(&self, f: &mut fmt::Formatter<'_>, policy: &Redaction) -> fmt::Result {
+		let reveal = match policy {
+			Redaction::Hash(salt) => return write!(f, "#{:016x}", salted_hash(salt, self.0)),
+			Redaction::Reveal => self.0.len(),
+			Redaction::Mask => 0,
+			Redaction::Truncate(len) => *len,
+		};
+		for (i, byte) in self.0.iter().enumerate() {
+			if i > 0 {
+				f.write_str(" ")?;
+			}
+			if i < reveal {
+				write!(f, "{:02x}", byte)?;
+			} else {
+				f.write_str("**")?;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Display for Redacted<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.fmt_with(f, &policy())
+	}
+}
+
+impl fmt::Debug for Redacted<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt::Display::fmt(self, f)
+	}
+}
+
+/// Returns the used part of a fixed-size buffer, as given by its length field
+pub(crate) fn used(data: &[u8], len: usize) -> &[u8] {
+	&data[..len.min(data.len())]
+}
+
+fn salted_hash(salt: &[u8], data: &[u8]) -> u64 {
+	let key = (siphash(0, 0, salt), siphash(0, 1, salt));
+	siphash(key.0, key.1, data)
+}
+
+/// SipHash-2-4, implemented here because the output of `std::hash` hashers is not guaranteed to be stable
+pub(crate) fn siphash(k0: u64, k1: u64, data: &[u8]) -> u64 {
+	let mut v = [
+		k0 ^ 0x736f6d6570736575,
+		k1 ^ 0x646f72616e646f6d,
+		k0 ^ 0x6c7967656e657261,
+		k1 ^ 0x7465646279746573,
+	];
+
+	fn round(v: &mut [u64; 4]) {
+		v[0] = v[0].wrapping_add(v[1]); v[1] = v[1].rotate_left(13); v[1] ^= v[0]; v[0] = v[0].rotate_left(32);
+		v[2] = v[2].wrapping_add(v[3]); v[3] = v[3].rotate_left(16); v[3] ^= v[2];
+		v[0] = v[0].wrapping_add(v[3]); v[3] = v[3].rotate_left(21); v[3] ^= v[0];
+		v[2] = v[2].wrapping_add(v[1]); v[1] = v[1].rotate_left(17); v[1] ^= v[2]; v[2] = v[2].rotate_left(32);
+	}
+
+	let mut chunks = data.chunks_exact(8);
+	for chunk in &mut chunks {
+		let m = u64::from_le_bytes(chunk.try_into().unwrap());
+		v[3] ^= m;
+		round(&mut v);
+		round(&mut v);
+		v[0] ^= m;
+	}
+
+	let mut last = [0u8; 8];
+	last[..chunks.remainder().len()].copy_from_slice(chunks.remainder());
+	last[7] = data.len() as u8;
+	let m = u64::from_le_bytes(last);
+	v[3] ^= m;
+	round(&mut v);
+	round(&mut v);
+	v[0] ^= m;
+
+	v[2] ^= 0xff;
+	for _ in 0..4 {
+		round(&mut v);
+	}
+	v[0] ^ v[1] ^ v[2] ^ v[3]
+}
@@ -0,0 +1,207 @@
+//! Serde support for the public data types, enabled with the `serde` feature.
+//!
+//! Byte arrays are serialized as lowercase hex strings. Arrays with a length
+//! field, such as `Iso14443a::uid` and `Iso14443a::uid_len`, are trimmed to
+//! their length, and the length field is left out and restored from the hex
+//! string on deserialization. Serialized data is not redacted.
+
+use crate::target_info as info;
+use crate::DepMode;
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de::Error as _};
+use std::fmt;
+
+fn encode(bytes: &[u8]) -> String {
+	bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode<E: serde::de::Error>(hex: &str) -> Result<Vec<u8>, E> {
+	if hex.len() & 1 != 0 || !hex.is_ascii() {
+		return Err(E::custom(format!("invalid hex string: {:?}", hex)));
+	}
+	(0..hex.len()).step_by(2)
+		.map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| E::custom(format!("invalid hex string: {:?}", hex))))
+		.collect()
+}
+
+/// Fixed-size arrays, as hex strings of exactly their size
+pub(crate) mod hex {
+	use super::*;
+
+	pub fn serialize<S: Serializer, const N: usize>(bytes: &[u8; N], serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&encode(bytes))
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(deserializer: D) -> Result<[u8; N], D::Error> {
+		let bytes = decode(&String::deserialize(deserializer)?)?;
+		let len = bytes.len();
+		bytes.try_into().map_err(|_| D::Error::invalid_length(len, &format!("{} bytes", N).as_str()))
+	}
+}
+
+/// Variable-length bytes, as hex strings
+pub(crate) mod hex_vec {
+	use super::*;
+
+	pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(&encode(bytes))
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+		decode(&String::deserialize(deserializer)?)
+	}
+}
+
+/// Returned when deserializing a trimmed array which does not fit its fixed-size array
+#[derive(Debug)]
+pub(crate) struct LengthError {
+	field: &'static str,
+	max: usize,
+}
+
+impl fmt::Display for LengthError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "{} is longer than {} bytes", self.field, self.max)
+	}
+}
+
+fn untrim<const N: usize>(field: &'static str, bytes: &[u8]) -> Result<([u8; N], usize), LengthError> {
+	if bytes.len() > N {
+		return Err(LengthError{ field, max: N });
+	}
+	let mut array = [0u8; N];
+	array[..bytes.len()].copy_from_slice(bytes);
+	Ok((array, bytes.len()))
+}
+
+// Serialized forms of the info structs with length fields
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Iso14443a")]
+pub(crate) struct Iso14443a {
+	#[serde(with = "hex")]
+	atqa: [u8; 2],
+	sak: u8,
+	#[serde(with = "hex_vec")]
+	uid: Vec<u8>,
+	#[serde(with = "hex_vec")]
+	ats: Vec<u8>,
+}
+
+impl From<info::Iso14443a> for Iso14443a {
+	fn from(info: info::Iso14443a) -> Self {
+		Self{
+			atqa: info.atqa,
+			sak: info.sak,
+			uid: info.uid().to_vec(),
+			ats: info.ats().to_vec(),
+		}
+	}
+}
+
+impl TryFrom<Iso14443a> for info::Iso14443a {
+	type Error = LengthError;
+	fn try_from(input: Iso14443a) -> Result<Self, LengthError> {
+		let (uid, uid_len) = untrim("uid", &input.uid)?;
+		let (ats, ats_len) = untrim("ats", &input.ats)?;
+		Ok(Self{ atqa: input.atqa, sak: input.sak, uid, uid_len, ats, ats_len })
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Iso14443bi")]
+pub(crate) struct Iso14443bi {
+	#[serde(with = "hex")]
+	div: [u8; 4],
+	ver_log: u8,
+	config: u8,
+	#[serde(with = "hex_vec")]
+	atr: Vec<u8>,
+}
+
+impl From<info::Iso14443bi> for Iso14443bi {
+	fn from(info: info::Iso14443bi) -> Self {
+		Self{
+			div: info.div,
+			ver_log: info.ver_log,
+			config: info.config,
+			atr: info.atr().to_vec(),
+		}
+	}
+}
+
+impl TryFrom<Iso14443bi> for info::Iso14443bi {
+	type Error = LengthError;
+	fn try_from(input: Iso14443bi) -> Result<Self, LengthError> {
+		let (atr, atr_len) = untrim("atr", &input.atr)?;
+		Ok(Self{ div: input.div, ver_log: input.ver_log, config: input.config, atr, atr_len })
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Dep")]
+pub(crate) struct Dep {
+	#[serde(with = "hex")]
+	nfcid3: [u8; 10],
+	did: u8,
+	bs: u8,
+	br: u8,
+	to: u8,
+	pp: u8,
+	#[serde(with = "hex_vec")]
+	gb: Vec<u8>,
+	dep_mode: DepMode,
+}
+
+impl From<info::Dep> for Dep {
+	fn from(info: info::Dep) -> Self {
+		Self{
+			nfcid3: info.nfcid3,
+			did: info.did,
+			bs: info.bs,
+			br: info.br,
+			to: info.to,
+			pp: info.pp,
+			gb: info.gb().to_vec(),
+			dep_mode: info.dep_mode,
+		}
+	}
+}
+
+impl TryFrom<Dep> for info::Dep {
+	type Error = LengthError;
+	fn try_from(input: Dep) -> Result<Self, LengthError> {
+		let (gb, gb_len) = untrim("gb", &input.gb)?;
+		Ok(Self{
+			nfcid3: input.nfcid3,
+			did: input.did,
+			bs: input.bs,
+			br: input.br,
+			to: input.to,
+			pp: input.pp,
+			gb,
+			gb_len,
+			dep_mode: input.dep_mode,
+		})
+	}
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Barcode")]
+pub(crate) struct Barcode {
+	#[serde(with = "hex_vec")]
+	data: Vec<u8>,
+}
+
+impl From<info::Barcode> for Barcode {
+	fn from(info: info::Barcode) -> Self {
+		Self{ data: info.data().to_vec() }
+	}
+}
+
+impl TryFrom<Barcode> for info::Barcode {
+	type Error = LengthError;
+	fn try_from(input: Barcode) -> Result<Self, LengthError> {
+		let (data, data_len) = untrim("data", &input.data)?;
+		Ok(Self{ data, data_len })
+	}
+}
@@ -0,0 +1,459 @@
+// Same output as snprint_nfc_target and friends in libnfc/target-subr.c, with
+// identifiers and payloads formatted through `Redacted`. The alternate form
+// (`{:#}`) is the verbose output.
+
+use super::Target;
+use super::info::*;
+use crate::{DepMode, ModulationType};
+use crate::redact::{Redacted, Redaction, policy};
+use std::fmt;
+
+const MAX_FRAME_SIZES: [u16; 9] = [16, 24, 32, 40, 48, 64, 96, 128, 256];
+
+const SAK_UID_NCOMPLETE: u8 = 0x04;
+const SAK_ISO14443_4_COMPLIANT: u8 = 0x20;
+const SAK_ISO18092_COMPLIANT: u8 = 0x40;
+
+const PI_CID_SUPPORTED: u8 = 0x01;
+const PI_NAD_SUPPORTED: u8 = 0x02;
+
+// Like snprint_hex: every byte followed by two spaces, then a newline
+fn hex(f: &mut fmt::Formatter<'_>, data: &[u8]) -> fmt::Result {
+	for byte in data {
+		write!(f, "{:02x}  ", byte)?;
+	}
+	writeln!(f)
+}
+
+// Like `hex`, for identifiers and payloads
+fn redacted_hex(f: &mut fmt::Formatter<'_>, data: &[u8]) -> fmt::Result {
+	if !data.is_empty() {
+		for token in Redacted(data).to_string().split(' ') {
+			write!(f, "{}  ", token)?;
+		}
+	}
+	writeln!(f)
+}
+
+// Like printf's %.4g, for the millisecond values below 10000 printed here
+fn g4(value: f64) -> String {
+	let int_digits = if value >= 1.0 { value.log10().floor() as i32 + 1 } else { 0 };
+	let decimals = (4 - int_digits).max(0) as usize;
+	let formatted = format!("{:.*}", decimals, value);
+	if formatted.contains('.') {
+		formatted.trim_end_matches('0').trim_end_matches('.').to_string()
+	} else {
+		formatted
+	}
+}
+
+fn frame_waiting_time(fwi: u8) -> String {
+	g4(256.0 * 16.0 * (1u32 << fwi) as f64 / 13560.0)
+}
+
+impl Iso14443a {
+	fn fmt_ats(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		// Decode ATS according to ISO/IEC 14443-4 (5.2 Answer to select)
+		let ats = self.ats();
+		let t0 = ats[0];
+		writeln!(f, "* Max Frame Size accepted by PICC: {} bytes", MAX_FRAME_SIZES[((t0 & 0x0f) as usize).min(8)])?;
+
+		let mut offset = 1;
+		if t0 & 0x10 != 0 {
+			let ta = ats.get(offset).copied().unwrap_or(0);
+			offset += 1;
+			writeln!(f, "* Bit Rate Capability:")?;
+			if ta == 0 {
+				writeln!(f, "  * PICC supports only 106 kbits/s in both directions")?;
+			}
+			for (bit, line) in [
+				(7, "Same bitrate in both directions mandatory"),
+				(4, "PICC to PCD, DS=2, bitrate 212 kbits/s supported"),
+				(5, "PICC to PCD, DS=4, bitrate 424 kbits/s supported"),
+				(6, "PICC to PCD, DS=8, bitrate 847 kbits/s supported"),
+				(0, "PCD to PICC, DR=2, bitrate 212 kbits/s supported"),
+				(1, "PCD to PICC, DR=4, bitrate 424 kbits/s supported"),
+				(2, "PCD to PICC, DR=8, bitrate 847 kbits/s supported"),
+				(3, "ERROR unknown value"),
+			] {
+				if ta & 1 << bit != 0 {
+					writeln!(f, "  * {}", line)?;
+				}
+			}
+		}
+		if t0 & 0x20 != 0 {
+			let tb = ats.get(offset).copied().unwrap_or(0);
+			offset += 1;
+			writeln!(f, "* Frame Waiting Time: {} ms", frame_waiting_time(tb >> 4))?;
+			if tb & 0x0f == 0 {
+				writeln!(f, "* No Start-up Frame Guard Time required")?;
+			} else {
+				writeln!(f, "* Start-up Frame Guard Time: {} ms", frame_waiting_time(tb & 0x0f))?;
+			}
+		}
+		if t0 & 0x40 != 0 {
+			let tc = ats.get(offset).copied().unwrap_or(0);
+			offset += 1;
+			writeln!(f, "* Node Address {}supported", if tc & 0x01 != 0 { "" } else { "not " })?;
+			writeln!(f, "* Card IDentifier {}supported", if tc & 0x02 != 0 { "" } else { "not " })?;
+		}
+		if ats.len() <= offset {
+			return Ok(());
+		}
+
+		write!(f, "* Historical bytes Tk: ")?;
+		redacted_hex(f, &ats[offset..])?;
+		let cib = ats[offset];
+		let tk = &ats[offset + 1..];
+		match cib {
+			0x00 => {
+				writeln!(f, "  * Tk after 0x00 consist of optional consecutive COMPACT-TLV data objects")?;
+				writeln!(f, "    followed by a mandatory status indicator (the last three bytes, not in TLV)")?;
+				writeln!(f, "    See ISO/IEC 7816-4 8.1.1.3 for more info")?;
+			},
+			0x10 => writeln!(f, "  * DIR data reference: {:02x}", tk.first().copied().unwrap_or(0))?,
+			0x80 if tk.is_empty() => writeln!(f, "  * No COMPACT-TLV objects found, no status found")?,
+			0x80 => {
+				writeln!(f, "  * Tk after 0x80 consist of optional consecutive COMPACT-TLV data objects;")?;
+				writeln!(f, "    the last data object may carry a status indicator of one, two or three bytes.")?;
+				writeln!(f, "    See ISO/IEC 7816-4 8.1.1.3 for more info")?;
+			},
+			cib if cib & 0xf0 == 0x80 => {},
+			cib => {
+				writeln!(f, "  * Proprietary format")?;
+				if cib == 0xc1 {
+					fmt_type_identification(f, tk)?;
+				}
+			},
+		}
+		Ok(())
+	}
+
+	fn fmt_fingerprint(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f)?;
+		writeln!(f, "Fingerprinting based on MIFARE type Identification Procedure:")?; // AN10833
+		let atqasak = (self.atqa[0] as u32) << 16 | (self.atqa[1] as u32) << 8 | self.sak as u32;
+		let known: &[&str] = match atqasak {
+			0x000218 => &["MIFARE Classic 4K"],
+			0x000408 => &["MIFARE Classic 1K", "MIFARE Plus (4 Byte UID or 4 Byte RID) 2K, Security level 1"],
+			0x000409 => &["MIFARE MINI"],
+			0x000410 => &["MIFARE Plus (4 Byte UID or 4 Byte RID) 2K, Security level 2"],
+			0x000411 => &["MIFARE Plus (4 Byte UID or 4 Byte RID) 4K, Security level 2"],
+			0x000418 => &["MIFARE Plus (4 Byte UID or 4 Byte RID) 4K, Security level 1"],
+			0x000420 => &["MIFARE Plus (4 Byte UID or 4 Byte RID) 2K/4K, Security level 3"],
+			0x004400 => &["MIFARE Ultralight", "MIFARE Ultralight C"],
+			0x004408 => &["MIFARE Plus (7 Byte UID) 2K, Security level 1"],
+			0x004410 => &["MIFARE Plus (7 Byte UID) 2K, Security level 2"],
+			0x004411 => &["MIFARE Plus (7 Byte UID) 4K, Security level 2"],
+			0x004418 => &["MIFARE Plus (7 Byte UID) 4K, Security level 1"],
+			0x004420 => &["MIFARE Plus (7 Byte UID) 2K/4K, Security level 3"],
+			0x034420 => &["MIFARE DESFire / Desfire EV1"],
+			// Not described in the MIFARE type identification procedure, but seen in the field
+			0x000488 => &["Mifare Classic 1K Infineon"],
+			0x000298 => &["Gemplus MPCOS"],
+			0x030428 => &["JCOP31"],
+			0x004820 => &["JCOP31 v2.4.1", "JCOP31 v2.2"],
+			0x000428 => &["JCOP31 v2.3.1"],
+			0x000453 => &["Fudan FM1208SH01"],
+			0x000820 => &["Fudan FM1208"],
+			0x000238 => &["MFC 4K emulated by Nokia 6212 Classic"],
+			0x000838 => &["MFC 4K emulated by Nokia 6131 NFC"],
+			_ => &["Unknown card, sorry"],
+		};
+		for name in known {
+			writeln!(f, "* {}", name)?;
+		}
+		Ok(())
+	}
+}
+
+// Historical bytes with category indicator 0xc1, as used by NXP
+fn fmt_type_identification(f: &mut fmt::Formatter<'_>, tk: &[u8]) -> fmt::Result {
+	writeln!(f, "    * Tag byte: Mifare or virtual cards of various types")?;
+	let Some((&len, tk)) = tk.split_first() else { return Ok(()) };
+	if len as usize != tk.len() {
+		writeln!(f, "    * Warning: Type Identification Coding length ({}) not matching Tk length ({})", len, tk.len())?;
+	}
+	if let Some(&ctc) = tk.first() {
+		writeln!(f, "    * Chip Type: {}", match ctc & 0xf0 {
+			0x00 => "(Multiple) Virtual Cards",
+			0x10 => "Mifare DESFire",
+			0x20 => "Mifare Plus",
+			_ => "RFU",
+		})?;
+		writeln!(f, "    * Memory size: {}", match ctc & 0x0f {
+			0x00 => "<1 kbyte",
+			0x01 => "1 kbyte",
+			0x02 => "2 kbyte",
+			0x03 => "4 kbyte",
+			0x04 => "8 kbyte",
+			0x0f => "Unspecified",
+			_ => "RFU",
+		})?;
+	}
+	if let Some(&cvc) = tk.get(1) {
+		writeln!(f, "    * Chip Status: {}", match cvc & 0xf0 {
+			0x00 => "Engineering sample",
+			0x20 => "Released",
+			_ => "RFU",
+		})?;
+		writeln!(f, "    * Chip Generation: {}", match cvc & 0x0f {
+			0x00 => "Generation 1",
+			0x01 => "Generation 2",
+			0x02 => "Generation 3",
+			0x0f => "Unspecified",
+			_ => "RFU",
+		})?;
+	}
+	if let Some(&vcs) = tk.get(2) {
+		writeln!(f, "    * Specifics (Virtual Card Selection):")?;
+		match vcs & 0x09 {
+			0x00 => writeln!(f, "      * Only VCSL supported")?,
+			0x01 => writeln!(f, "      * VCS, VCSL and SVC supported")?,
+			_ => {},
+		}
+		writeln!(f, "      * {}", match vcs {
+			vcs if vcs & 0x0e == 0x00 => "SL1, SL2(?), SL3 supported",
+			vcs if vcs & 0x0e == 0x02 => "SL3 only card",
+			vcs if vcs & 0x0f == 0x0e => "No VCS command supported",
+			vcs if vcs & 0x0f == 0x0f => "Unspecified",
+			_ => "RFU",
+		})?;
+	}
+	Ok(())
+}
+
+impl fmt::Display for Iso14443a {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let verbose = f.alternate();
+		write!(f, "    ATQA (SENS_RES): ")?;
+		hex(f, &self.atqa)?;
+		if verbose {
+			writeln!(f, "* UID size: {}", ["single", "double", "triple", "RFU"][(self.atqa[1] >> 6) as usize])?;
+			let supported = matches!(self.atqa[1] & 0x1f, 0x01 | 0x02 | 0x04 | 0x08 | 0x10);
+			writeln!(f, "* bit frame anticollision {}supported", if supported { "" } else { "not " })?;
+		}
+		let random = self.uid[0] == 0x08;
+		write!(f, "       UID (NFCID{}): ", if random { '3' } else { '1' })?;
+		redacted_hex(f, self.uid())?;
+		if verbose && random {
+			writeln!(f, "* Random UID")?;
+		}
+		write!(f, "      SAK (SEL_RES): ")?;
+		hex(f, &[self.sak])?;
+		if verbose {
+			if self.sak & SAK_UID_NCOMPLETE != 0 {
+				writeln!(f, "* Warning! Cascade bit set: UID not complete")?;
+			}
+			if self.sak & SAK_ISO14443_4_COMPLIANT != 0 {
+				writeln!(f, "* Compliant with ISO/IEC 14443-4")?;
+			} else {
+				writeln!(f, "* Not compliant with ISO/IEC 14443-4")?;
+			}
+			if self.sak & SAK_ISO18092_COMPLIANT != 0 {
+				writeln!(f, "* Compliant with ISO/IEC 18092")?;
+			} else {
+				writeln!(f, "* Not compliant with ISO/IEC 18092")?;
+			}
+		}
+		if self.ats_len > 0 {
+			write!(f, "                ATS: ")?;
+			redacted_hex(f, self.ats())?;
+			if verbose {
+				self.fmt_ats(f)?;
+			}
+		}
+		if verbose {
+			self.fmt_fingerprint(f)?;
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Display for Felica {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "        ID (NFCID2): ")?;
+		redacted_hex(f, &self.id)?;
+		write!(f, "    Parameter (PAD): ")?;
+		hex(f, &self.pad)?;
+		write!(f, "   System Code (SC): ")?;
+		hex(f, &self.sys_code)
+	}
+}
+
+impl fmt::Display for Iso14443b {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "               PUPI: ")?;
+		redacted_hex(f, &self.pupi)?;
+		write!(f, "   Application Data: ")?;
+		redacted_hex(f, &self.application_data)?;
+		write!(f, "      Protocol Info: ")?;
+		hex(f, &self.protocol_info)?;
+		if !f.alternate() {
+			return Ok(());
+		}
+
+		let [bit_rate, frame_size, timing] = self.protocol_info;
+		writeln!(f, "* Bit Rate Capability:")?;
+		if bit_rate == 0 {
+			writeln!(f, " * PICC supports only 106 kbits/s in both directions")?;
+		}
+		for (bit, line) in [
+			(7, "Same bitrate in both directions mandatory"),
+			(4, "PICC to PCD, 1etu=64/fc, bitrate 212 kbits/s supported"),
+			(5, "PICC to PCD, 1etu=32/fc, bitrate 424 kbits/s supported"),
+			(6, "PICC to PCD, 1etu=16/fc, bitrate 847 kbits/s supported"),
+			(0, "PCD to PICC, 1etu=64/fc, bitrate 212 kbits/s supported"),
+			(1, "PCD to PICC, 1etu=32/fc, bitrate 424 kbits/s supported"),
+			(2, "PCD to PICC, 1etu=16/fc, bitrate 847 kbits/s supported"),
+			(3, "ERROR unknown value"),
+		] {
+			if bit_rate & 1 << bit != 0 {
+				writeln!(f, " * {}", line)?;
+			}
+		}
+		if frame_size >> 4 <= 8 {
+			writeln!(f, "* Maximum frame sizes: {} bytes", MAX_FRAME_SIZES[(frame_size >> 4) as usize])?;
+		}
+		if frame_size & 0x01 != 0 {
+			writeln!(f, "* Protocol types supported: ISO/IEC 14443-4")?;
+		}
+		writeln!(f, "* Frame Waiting Time: {} ms", frame_waiting_time(timing >> 4))?;
+		if timing & (PI_NAD_SUPPORTED | PI_CID_SUPPORTED) != 0 {
+			write!(f, "* Frame options supported: ")?;
+			if timing & PI_NAD_SUPPORTED != 0 {
+				write!(f, "NAD ")?;
+			}
+			if timing & PI_CID_SUPPORTED != 0 {
+				write!(f, "CID ")?;
+			}
+			writeln!(f)?;
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Display for Iso14443bi {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "                DIV: ")?;
+		redacted_hex(f, &self.div)?;
+		let long_repgen = self.ver_log & 0x80 != 0;
+		if f.alternate() {
+			let version = (self.ver_log & 0x1e) >> 1;
+			write!(f, "   Software Version: ")?;
+			if version == 15 {
+				writeln!(f, "Undefined")?;
+			} else {
+				writeln!(f, "{}", version)?;
+			}
+			if long_repgen && self.config & 0x80 != 0 {
+				writeln!(f, "        Wait Enable: yes")?;
+			}
+		}
+		if long_repgen && self.config & 0x40 != 0 {
+			write!(f, "                ATS: ")?;
+			redacted_hex(f, self.atr())?;
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Display for Iso14443b2sr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "                UID: ")?;
+		redacted_hex(f, &self.uid)
+	}
+}
+
+impl fmt::Display for Iso14443b2ct {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "                UID: ")?;
+		redacted_hex(f, &self.uid)?;
+		// The decimal UID identifies the card as much as the UID itself
+		let decimal = match policy() {
+			Redaction::Reveal => format!("{:010}", u32::from_le_bytes(self.uid)),
+			Redaction::Hash(_) => Redacted(&self.uid).to_string(),
+			_ => "*".repeat(10),
+		};
+		writeln!(f, "      UID (decimal): {}", decimal)?;
+		writeln!(f, "       Product Code: {:02X}", self.prod_code)?;
+		writeln!(f, "           Fab Code: {:02X}", self.fab_code)
+	}
+}
+
+impl fmt::Display for Jewel {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "    ATQA (SENS_RES): ")?;
+		hex(f, &self.sens_res)?;
+		write!(f, "      4-LSB JEWELID: ")?;
+		redacted_hex(f, &self.id)
+	}
+}
+
+impl fmt::Display for Dep {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "       NFCID3: ")?;
+		redacted_hex(f, &self.nfcid3)?;
+		writeln!(f, "           BS: {:02x}", self.bs)?;
+		writeln!(f, "           BR: {:02x}", self.br)?;
+		writeln!(f, "           TO: {:02x}", self.to)?;
+		writeln!(f, "           PP: {:02x}", self.pp)?;
+		if self.gb_len > 0 {
+			write!(f, "General Bytes: ")?;
+			redacted_hex(f, self.gb())?;
+		}
+		Ok(())
+	}
+}
+
+impl fmt::Display for Barcode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let data = self.data();
+		writeln!(f, "        Size (bits): {}", data.len() * 8)?;
+		write!(f, "            Content: ")?;
+		let content = Redacted(data).to_string().to_uppercase();
+		let tokens: Vec<&str> = content.split(' ').filter(|token| !token.is_empty()).collect();
+		for (i, token) in tokens.iter().enumerate() {
+			f.write_str(token)?;
+			if i % 8 == 7 && i + 1 < tokens.len() {
+				write!(f, "\n                     ")?;
+			}
+		}
+		writeln!(f)
+	}
+}
+
+impl fmt::Display for Iso14443biClass {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "                UID: ")?;
+		redacted_hex(f, &self.uid)
+	}
+}
+
+impl fmt::Display for TargetInfo {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			TargetInfo::Iso14443a(info) => fmt::Display::fmt(info, f),
+			TargetInfo::Felica(info) => fmt::Display::fmt(info, f),
+			TargetInfo::Iso14443b(info) => fmt::Display::fmt(info, f),
+			TargetInfo::Iso14443bi(info) => fmt::Display::fmt(info, f),
+			TargetInfo::Iso14443b2sr(info) => fmt::Display::fmt(info, f),
+			TargetInfo::Iso14443b2ct(info) => fmt::Display::fmt(info, f),
+			TargetInfo::Jewel(info) => fmt::Display::fmt(info, f),
+			TargetInfo::Dep(info) => fmt::Display::fmt(info, f),
+			TargetInfo::Barcode(info) => fmt::Display::fmt(info, f),
+			TargetInfo::Iso14443biClass(info) => fmt::Display::fmt(info, f),
+		}
+	}
+}
+
+impl fmt::Display for Target {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} ({}", self.modulation.modulation_type, self.modulation.baud_rate)?;
+		if let (ModulationType::Dep, TargetInfo::Dep(info)) = (self.modulation.modulation_type, &self.target_info) {
+			write!(f, ", {}", if info.dep_mode == DepMode::Active { "active mode" } else { "passive mode" })?;
+		}
+		writeln!(f, ") target:")?;
+		fmt::Display::fmt(&self.target_info, f)
+	}
+}
@@ -0,0 +1,144 @@
+use super::Target;
+use super::info::TargetInfo;
+use crate::ModulationType;
+use crate::redact::Redacted;
+use std::fmt;
+
+/// Size of an ISO/IEC 14443A UID, and the number of cascade levels needed to select it
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UidSize {
+	/// 4 bytes, cascade level 1
+	Single,
+	/// 7 bytes, cascade level 2
+	Double,
+	/// 10 bytes, cascade level 3
+	Triple,
+}
+
+impl UidSize {
+	pub fn from_len(len: usize) -> Option<Self> {
+		match len {
+			4 => Some(Self::Single),
+			7 => Some(Self::Double),
+			10 => Some(Self::Triple),
+			_ => None,
+		}
+	}
+
+	pub fn uid_len(&self) -> usize {
+		match self {
+			Self::Single => 4,
+			Self::Double => 7,
+			Self::Triple => 10,
+		}
+	}
+
+	pub fn cascade_levels(&self) -> u8 {
+		match self {
+			Self::Single => 1,
+			Self::Double => 2,
+			Self::Triple => 3,
+		}
+	}
+}
+
+/// Identifier of a card, comparable across technologies.
+///
+/// The identifier is taken from the field which identifies the card for its
+/// technology: the UID for ISO/IEC 14443A, ST SRx, ASK CTx and iClass, the
+/// NFCID2 for FeliCa, the PUPI for ISO/IEC 14443B, the DIV for ISO/IEC 14443B',
+/// the Jewel ID, the NFCID3 for D.E.P. and the data of a Thinfilm barcode.
+/// Identifiers of different technologies never compare equal.
+///
+/// `Display` and `Debug` output is redacted, see `crate::redact`; use `to_hex`
+/// for a database key.
+#[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CardId {
+	modulation_type: ModulationType,
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex_vec"))]
+	id: Vec<u8>,
+}
+
+impl CardId {
+	/// Creates an identifier from stored bytes, for lookups
+	pub fn new(modulation_type: ModulationType, id: &[u8]) -> Self {
+		Self{ modulation_type, id: id.to_vec() }
+	}
+
+	pub fn modulation_type(&self) -> ModulationType {
+		self.modulation_type
+	}
+
+	pub fn as_bytes(&self) -> &[u8] {
+		&self.id
+	}
+
+	/// Returns the identifier as unredacted lowercase hex
+	pub fn to_hex(&self) -> String {
+		self.id.iter().map(|byte| format!("{:02x}", byte)).collect()
+	}
+
+	/// Returns the UID size, for ISO/IEC 14443A identifiers
+	pub fn uid_size(&self) -> Option<UidSize> {
+		match self.modulation_type {
+			ModulationType::Iso14443a => UidSize::from_len(self.id.len()),
+			_ => None,
+		}
+	}
+
+	/// Returns the number of cascade levels needed to select the card, for ISO/IEC 14443A identifiers
+	pub fn cascade_levels(&self) -> Option<u8> {
+		self.uid_size().map(|size| size.cascade_levels())
+	}
+
+	/// Returns whether the identifier is a random ID (single size UID starting with 0x08), which changes on every activation
+	pub fn is_random(&self) -> bool {
+		self.uid_size() == Some(UidSize::Single) && self.id[0] == 0x08
+	}
+}
+
+impl From<&TargetInfo> for CardId {
+	fn from(info: &TargetInfo) -> Self {
+		match info {
+			TargetInfo::Iso14443a(info) => Self::new(ModulationType::Iso14443a, info.uid()),
+			TargetInfo::Felica(info) => Self::new(ModulationType::Felica, &info.id),
+			TargetInfo::Iso14443b(info) => Self::new(ModulationType::Iso14443b, &info.pupi),
+			TargetInfo::Iso14443bi(info) => Self::new(ModulationType::Iso14443bi, &info.div),
+			TargetInfo::Iso14443b2sr(info) => Self::new(ModulationType::Iso14443b2sr, &info.uid),
+			TargetInfo::Iso14443b2ct(info) => Self::new(ModulationType::Iso14443b2ct, &info.uid),
+			TargetInfo::Jewel(info) => Self::new(ModulationType::Jewel, &info.id),
+			TargetInfo::Dep(info) => Self::new(ModulationType::Dep, &info.nfcid3),
+			TargetInfo::Barcode(info) => Self::new(ModulationType::Barcode, info.data()),
+			TargetInfo::Iso14443biClass(info) => Self::new(ModulationType::Iso14443biClass, &info.uid),
+		}
+	}
+}
+
+impl From<&Target> for CardId {
+	fn from(target: &Target) -> Self {
+		Self::from(&target.target_info)
+	}
+}
+
+impl Target {
+	pub fn card_id(&self) -> CardId {
+		self.into()
+	}
+}
+
+impl fmt::Display for CardId {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", Redacted(&self.id))
+	}
+}
+
+impl fmt::Debug for CardId {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("CardId")
+			.field("modulation_type", &self.modulation_type)
+			.field("id", &Redacted(&self.id))
+			.finish()
+	}
+}
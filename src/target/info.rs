@@ -0,0 +1,954 @@
+use crate::DepMode;
+use crate::redact::{Redacted, used};
+use std::fmt;
+
+/// Safe version of nfc_iso14443a_info
+/// NFC ISO14443A tag (MIFARE) information
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "crate::serialize::Iso14443a", try_from = "crate::serialize::Iso14443a"))]
+pub struct Iso14443a {
+	pub atqa: [u8; 2],
+	pub sak: u8,
+	pub uid: [u8; 10],
+	pub uid_len: usize,
+	pub ats: [u8; 254],
+	pub ats_len: usize,
+}
+
+/// Safe version of nfc_felica_info
+/// NFC FeLiCa tag information
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Felica {
+	pub len: usize,
+	pub res_code: u8,
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex"))]
+	pub id: [u8; 8],
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex"))]
+	pub pad: [u8; 8],
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex"))]
+	pub sys_code: [u8; 2],
+}
+
+/// Safe version of nfc_iso14443b_info
+/// NFC ISO14443B tag information
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Iso14443b {
+	/// pupi stores PUPI contained in ATQB (Answer To reQuest of type B) (see ISO14443-3)
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex"))]
+	pub pupi: [u8; 4],
+	/// application_data stores Application Data contained in ATQB (see ISO14443-3)
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex"))]
+	pub application_data: [u8; 4],
+	/// protocol_info stores Protocol Info contained in ATQB (see ISO14443-3)
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex"))]
+	pub protocol_info: [u8; 3],
+	/// card_identifier stores CID (Card Identifier) attributted by PCD to the PICC
+	pub card_identifier: u8,
+}
+
+/// Safe version of nfc_iso14443bi_info
+/// NFC ISO14443B' tag information
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "crate::serialize::Iso14443bi", try_from = "crate::serialize::Iso14443bi"))]
+pub struct Iso14443bi {
+	/// div: 4 LSBytes of tag serial number
+	pub div: [u8; 4],
+	/// Software version & type of REPGEN
+	pub ver_log: u8,
+	/// Config Byte, present if long REPGEN
+	pub config: u8,
+	/// ATR, if any
+	pub atr: [u8; 33],
+	pub atr_len: usize,
+}
+
+/// Safe version of nfc_iso14443b2sr_info
+/// NFC ISO14443-2B ST SRx tag information
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Iso14443b2sr {
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex"))]
+	pub uid: [u8; 8],
+}
+
+/// Safe version of nfc_iso14443b2ct_info
+/// NFC ISO14443-2B ASK CTx tag information
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Iso14443b2ct {
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex"))]
+	pub uid: [u8; 4],
+	pub prod_code: u8,
+	pub fab_code: u8,
+}
+
+/// Safe version of nfc_jewel_info
+/// NFC Jewel tag information
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Jewel {
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex"))]
+	pub sens_res: [u8; 2],
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex"))]
+	pub id: [u8; 4],
+}
+
+/// Safe version of nfc_dep_info
+/// NFC target information in D.E.P. (Data Exchange Protocol) see ISO/IEC 18092 (NFCIP-1)
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "crate::serialize::Dep", try_from = "crate::serialize::Dep"))]
+pub struct Dep {
+	/// NFCID3
+	pub nfcid3: [u8; 10],
+	/// DID
+	pub did: u8,
+	/// Supported send-bit rate
+	pub bs: u8,
+	/// Supported receive-bit rate
+	pub br: u8,
+	/// Timeout value
+	pub to: u8,
+	/// PP Parameters
+	pub pp: u8,
+	/// General Bytes
+	pub gb: [u8; 48],
+	pub gb_len: usize,
+	/// DEP mode
+	pub dep_mode: DepMode,
+}
+
+/// Safe version of nfc_barcode_info
+/// Thinfilm NFC Barcode information
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(into = "crate::serialize::Barcode", try_from = "crate::serialize::Barcode"))]
+pub struct Barcode {
+	pub data: [u8; 32],
+	pub data_len: usize,
+}
+
+/// Safe version of nfc_iso14443biclass_info
+/// NFC ISO14443BiClass tag information
+#[derive(PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Iso14443biClass {
+	#[cfg_attr(feature = "serde", serde(with = "crate::serialize::hex"))]
+	pub uid: [u8; 8],
+}
+
+/// Safe version of nfc_target_info
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[allow(clippy::large_enum_variant)]
+pub enum TargetInfo {
+	Iso14443a(Iso14443a),
+	Felica(Felica),
+	Iso14443b(Iso14443b),
+	Iso14443bi(Iso14443bi),
+	Iso14443b2sr(Iso14443b2sr),
+	Iso14443b2ct(Iso14443b2ct),
+	Jewel(Jewel),
+	Dep(Dep),
+	Barcode(Barcode),
+	Iso14443biClass(Iso14443biClass),
+}
+
+// Identifiers and payloads are formatted through `Redacted`, see `crate::redact`
+
+impl fmt::Debug for Iso14443a {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Iso14443a")
+			.field("atqa", &self.atqa)
+			.field("sak", &self.sak)
+			.field("uid", &Redacted(used(&self.uid, self.uid_len)))
+			.field("uid_len", &self.uid_len)
+			.field("ats", &Redacted(used(&self.ats, self.ats_len)))
+			.field("ats_len", &self.ats_len)
+			.finish()
+	}
+}
+
+impl fmt::Debug for Felica {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Felica")
+			.field("len", &self.len)
+			.field("res_code", &self.res_code)
+			.field("id", &Redacted(&self.id))
+			.field("pad", &self.pad)
+			.field("sys_code", &self.sys_code)
+			.finish()
+	}
+}
+
+impl fmt::Debug for Iso14443b {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Iso14443b")
+			.field("pupi", &Redacted(&self.pupi))
+			.field("application_data", &Redacted(&self.application_data))
+			.field("protocol_info", &self.protocol_info)
+			.field("card_identifier", &self.card_identifier)
+			.finish()
+	}
+}
+
+impl fmt::Debug for Iso14443bi {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Iso14443bi")
+			.field("div", &Redacted(&self.div))
+			.field("ver_log", &self.ver_log)
+			.field("config", &self.config)
+			.field("atr", &Redacted(used(&self.atr, self.atr_len)))
+			.field("atr_len", &self.atr_len)
+			.finish()
+	}
+}
+
+impl fmt::Debug for Iso14443b2sr {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Iso14443b2sr")
+			.field("uid", &Redacted(&self.uid))
+			.finish()
+	}
+}
+
+impl fmt::Debug for Iso14443b2ct {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Iso14443b2ct")
+			.field("uid", &Redacted(&self.uid))
+			.field("prod_code", &self.prod_code)
+			.field("fab_code", &self.fab_code)
+			.finish()
+	}
+}
+
+impl fmt::Debug for Jewel {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Jewel")
+			.field("sens_res", &self.sens_res)
+			.field("id", &Redacted(&self.id))
+			.finish()
+	}
+}
+
+impl fmt::Debug for Dep {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Dep")
+			.field("nfcid3", &Redacted(&self.nfcid3))
+			.field("did", &self.did)
+			.field("bs", &self.bs)
+			.field("br", &self.br)
+			.field("to", &self.to)
+			.field("pp", &self.pp)
+			.field("gb", &Redacted(used(&self.gb, self.gb_len)))
+			.field("gb_len", &self.gb_len)
+			.field("dep_mode", &self.dep_mode)
+			.finish()
+	}
+}
+
+impl fmt::Debug for Barcode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Barcode")
+			.field("data", &Redacted(used(&self.data, self.data_len)))
+			.field("data_len", &self.data_len)
+			.finish()
+	}
+}
+
+impl fmt::Debug for Iso14443biClass {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Iso14443biClass")
+			.field("uid", &Redacted(&self.uid))
+			.finish()
+	}
+}
+
+#[cfg(feature = "libnfc")]
+impl From<&Iso14443a> for nfc1_sys::nfc_iso14443a_info {
+	fn from(info: &Iso14443a) -> Self {
+		Self {
+			abtAtqa: info.atqa,
+			btSak: info.sak,
+			szUidLen: info.uid_len,
+			abtUid: info.uid,
+			szAtsLen: info.ats_len,
+			abtAts: info.ats,
+		}
+	}
+}
+
+#[cfg(feature = "libnfc")]
+impl From<&Felica> for nfc1_sys::nfc_felica_info {
+	fn from(info: &Felica) -> Self {
+		Self {
+			szLen: info.len,
+			btResCode: info.res_code,
+			abtId: info.id,
+			abtPad: info.pad,
+			abtSysCode: info.sys_code,
+		}
+	}
+}
+
+#[cfg(feature = "libnfc")]
+impl From<&Iso14443b> for nfc1_sys::nfc_iso14443b_info {
+	fn from(info: &Iso14443b) -> Self {
+		Self {
+			abtPupi: info.pupi,
+			abtApplicationData: info.application_data,
+			abtProtocolInfo: info.protocol_info,
+			ui8CardIdentifier: info.card_identifier,
+		}
+	}
+}
+
+#[cfg(feature = "libnfc")]
+impl From<&Iso14443bi> for nfc1_sys::nfc_iso14443bi_info {
+	fn from(info: &Iso14443bi) -> Self {
+		Self {
+			abtDIV: info.div,
+			btVerLog: info.ver_log,
+			btConfig: info.config,
+			szAtrLen: info.atr_len,
+			abtAtr: info.atr,
+		}
+	}
+}
+
+#[cfg(feature = "libnfc")]
+impl From<&Iso14443b2sr> for nfc1_sys::nfc_iso14443b2sr_info {
+	fn from(info: &Iso14443b2sr) -> Self {
+		Self { abtUID: info.uid }
+	}
+}
+
+#[cfg(feature = "libnfc")]
+impl From<&Iso14443b2ct> for nfc1_sys::nfc_iso14443b2ct_info {
+	fn from(info: &Iso14443b2ct) -> Self {
+		Self {
+			abtUID: info.uid,
+			btProdCode: info.prod_code,
+			btFabCode: info.fab_code,
+		}
+	}
+}
+
+#[cfg(feature = "libnfc")]
+impl From<&Jewel> for nfc1_sys::nfc_jewel_info {
+	fn from(info: &Jewel) -> Self {
+		Self {
+			btSensRes: info.sens_res,
+			btId: info.id,
+		}
+	}
+}
+
+#[cfg(feature = "libnfc")]
+impl From<&Dep> for nfc1_sys::nfc_dep_info {
+	fn from(info: &Dep) -> Self {
+		Self {
+			abtNFCID3: info.nfcid3,
+			btDID: info.did,
+			btBS: info.bs,
+			btBR: info.br,
+			btTO: info.to,
+			btPP: info.pp,
+			abtGB: info.gb,
+			szGB: if info.gb_len > 48 {
+				48
+			} else {
+				info.gb_len
+			},
+			ndm: info.dep_mode.into(),
+		}
+	}
+}
+
+#[cfg(feature = "libnfc")]
+impl From<&Barcode> for nfc1_sys::nfc_barcode_info {
+	fn from(info: &Barcode) -> Self {
+		Self {
+			szDataLen: info.data_len,
+			abtData: info.data,
+		}
+	}
+}
+
+#[cfg(feature = "libnfc")]
+impl From<&Iso14443biClass> for nfc1_sys::nfc_iso14443biclass_info {
+	fn from(info: &Iso14443biClass) -> Self {
+		Self { abtUID: info.uid }
+	}
+}
+
+#[cfg(feature = "libnfc")]
+impl From<&TargetInfo> for nfc1_sys::nfc_target_info {
+	fn from(input: &TargetInfo) -> nfc1_sys::nfc_target_info {
+		match input {
+			TargetInfo::Iso14443a(info) => nfc1_sys::nfc_target_info { nai: info.into() },
+			TargetInfo::Felica(info) => nfc1_sys::nfc_target_info { nfi: info.into() },
+			TargetInfo::Iso14443b(info) => nfc1_sys::nfc_target_info { nbi: info.into() },
+			TargetInfo::Iso14443bi(info) => nfc1_sys::nfc_target_info { nii: info.into() },
+			TargetInfo::Iso14443b2sr(info) => nfc1_sys::nfc_target_info { nsi: info.into() },
+			TargetInfo::Iso14443b2ct(info) => nfc1_sys::nfc_target_info { nci: info.into() },
+			TargetInfo::Jewel(info) => nfc1_sys::nfc_target_info { nji: info.into() },
+			TargetInfo::Dep(info) => nfc1_sys::nfc_target_info { ndi: info.into() },
+			TargetInfo::Barcode(info) => nfc1_sys::nfc_target_info { nti: info.into() },
+			TargetInfo::Iso14443biClass(info) => nfc1_sys::nfc_target_info { nhi: info.into() },
+		}
+	}
+}
+
+impl TargetInfo {
+	#[cfg(feature = "libnfc")]
+	pub fn from_iso14443a(input: nfc1_sys::nfc_target_info) -> Self {
+		unsafe {
+			Self::Iso14443a(Iso14443a {
+				atqa: input.nai.abtAtqa,
+				sak: input.nai.btSak,
+				uid_len: input.nai.szUidLen as usize,
+				uid: input.nai.abtUid,
+				ats_len: input.nai.szAtsLen as usize,
+				ats: input.nai.abtAts,
+			})
+		}
+	}
+
+	#[cfg(feature = "libnfc")]
+	pub fn from_felica(input: nfc1_sys::nfc_target_info) -> Self {
+		unsafe {
+			Self::Felica(Felica {
+				len: input.nfi.szLen as usize,
+				res_code: input.nfi.btResCode,
+				id: input.nfi.abtId,
+				pad: input.nfi.abtPad,
+				sys_code: input.nfi.abtSysCode,
+			})
+		}
+	}
+
+	#[cfg(feature = "libnfc")]
+	pub fn from_iso14443b(input: nfc1_sys::nfc_target_info) -> Self {
+		unsafe {
+			Self::Iso14443b(Iso14443b {
+				pupi: input.nbi.abtPupi,
+				application_data: input.nbi.abtApplicationData,
+				protocol_info: input.nbi.abtProtocolInfo,
+				card_identifier: input.nbi.ui8CardIdentifier,
+			})
+		}
+	}
+
+	#[cfg(feature = "libnfc")]
+	pub fn from_iso14443bi(input: nfc1_sys::nfc_target_info) -> Self {
+		unsafe {
+			Self::Iso14443bi(Iso14443bi {
+				div: input.nii.abtDIV,
+				ver_log: input.nii.btVerLog,
+				config: input.nii.btConfig,
+				atr_len: input.nii.szAtrLen as usize,
+				atr: input.nii.abtAtr,
+			})
+		}
+	}
+
+	#[cfg(feature = "libnfc")]
+	pub fn from_iso14443b2sr(input: nfc1_sys::nfc_target_info) -> Self {
+		unsafe {
+			Self::Iso14443b2sr(Iso14443b2sr {
+				uid: input.nsi.abtUID,
+			})
+		}
+	}
+
+	#[cfg(feature = "libnfc")]
+	pub fn from_iso14443b2ct(input: nfc1_sys::nfc_target_info) -> Self {
+		unsafe {
+			Self::Iso14443b2ct(Iso14443b2ct {
+				uid: input.nci.abtUID,
+				prod_code: input.nci.btProdCode,
+				fab_code: input.nci.btFabCode,
+			})
+		}
+	}
+
+	#[cfg(feature = "libnfc")]
+	pub fn from_jewel(input: nfc1_sys::nfc_target_info) -> Self {
+		unsafe {
+			Self::Jewel(Jewel {
+				sens_res: input.nji.btSensRes,
+				id: input.nji.btId,
+			})
+		}
+	}
+
+	#[cfg(feature = "libnfc")]
+	pub fn from_dep(input: nfc1_sys::nfc_target_info) -> Self {
+		unsafe {
+			Self::Dep(Dep {
+				nfcid3: input.ndi.abtNFCID3,
+				did: input.ndi.btDID,
+				bs: input.ndi.btBS,
+				br: input.ndi.btBR,
+				to: input.ndi.btTO,
+				pp: input.ndi.btPP,
+				gb: input.ndi.abtGB,
+				gb_len: input.ndi.szGB as usize,
+				dep_mode: input.ndi.ndm.into(),
+			})
+		}
+	}
+
+	#[cfg(feature = "libnfc")]
+	pub fn from_barcode(input: nfc1_sys::nfc_target_info) -> Self {
+		unsafe {
+			Self::Barcode(Barcode {
+				data: input.nti.abtData,
+				data_len: input.nti.szDataLen as usize,
+			})
+		}
+	}
+
+	#[cfg(feature = "libnfc")]
+	pub fn from_iso14443biclass(input: nfc1_sys::nfc_target_info) -> Self {
+		unsafe {
+			Self::Iso14443biClass(Iso14443biClass {
+				uid: input.nhi.abtUID,
+			})
+		}
+	}
+
+	pub fn new_iso14443a() -> Self {
+		Self::Iso14443a(Iso14443a {
+			atqa: [0u8; 2],
+			sak: 0,
+			uid: [0u8; 10],
+			uid_len: 0,
+			ats: [0u8; 254],
+			ats_len: 0,
+		})
+	}
+
+	pub fn new_felica() -> Self {
+		Self::Felica(Felica {
+			len: 0,
+			res_code: 0,
+			id: [0u8; 8],
+			pad: [0u8; 8],
+			sys_code: [0u8; 2],
+		})
+	}
+
+	pub fn new_iso14443b() -> Self {
+		Self::Iso14443b(Iso14443b {
+			pupi: [0u8; 4],
+			application_data: [0u8; 4],
+			protocol_info: [0u8; 3],
+			card_identifier: 0,
+		})
+	}
+
+	pub fn new_iso14443bi() -> Self {
+		Self::Iso14443bi(Iso14443bi {
+			div: [0u8; 4],
+			ver_log: 0,
+			config: 0,
+			atr: [0u8; 33],
+			atr_len: 0,
+		})
+	}
+
+	pub fn new_iso14443b2sr() -> Self {
+		Self::Iso14443b2sr(Iso14443b2sr { uid: [0u8; 8] })
+	}
+
+	pub fn new_iso14443b2ct() -> Self {
+		Self::Iso14443b2ct(Iso14443b2ct {
+			uid: [0u8; 4],
+			prod_code: 0,
+			fab_code: 0,
+		})
+	}
+
+	pub fn new_jewel() -> Self {
+		Self::Jewel(Jewel {
+			sens_res: [0u8; 2],
+			id: [0u8; 4],
+		})
+	}
+
+	pub fn new_dep() -> Self {
+		Self::Dep(Dep {
+			nfcid3: [0u8; 10],
+			did: 0,
+			bs: 0,
+			br: 0,
+			to: 0,
+			pp: 0,
+			gb: [0u8; 48],
+			gb_len: 0,
+			dep_mode: DepMode::Undefined,
+		})
+	}
+
+	pub fn new_barcode() -> Self {
+		Self::Barcode(Barcode {
+			data: [0u8; 32],
+			data_len: 0,
+		})
+	}
+
+	pub fn new_iso14443biclass() -> Self {
+		Self::Iso14443biClass(Iso14443biClass { uid: [0u8; 8] })
+	}
+}
+
+impl From<Iso14443a> for TargetInfo {
+	fn from(info: Iso14443a) -> Self {
+		Self::Iso14443a(info)
+	}
+}
+
+impl From<Felica> for TargetInfo {
+	fn from(info: Felica) -> Self {
+		Self::Felica(info)
+	}
+}
+
+impl From<Iso14443b> for TargetInfo {
+	fn from(info: Iso14443b) -> Self {
+		Self::Iso14443b(info)
+	}
+}
+
+impl From<Iso14443bi> for TargetInfo {
+	fn from(info: Iso14443bi) -> Self {
+		Self::Iso14443bi(info)
+	}
+}
+
+impl From<Iso14443b2sr> for TargetInfo {
+	fn from(info: Iso14443b2sr) -> Self {
+		Self::Iso14443b2sr(info)
+	}
+}
+
+impl From<Iso14443b2ct> for TargetInfo {
+	fn from(info: Iso14443b2ct) -> Self {
+		Self::Iso14443b2ct(info)
+	}
+}
+
+impl From<Jewel> for TargetInfo {
+	fn from(info: Jewel) -> Self {
+		Self::Jewel(info)
+	}
+}
+
+impl From<Dep> for TargetInfo {
+	fn from(info: Dep) -> Self {
+		Self::Dep(info)
+	}
+}
+
+impl From<Barcode> for TargetInfo {
+	fn from(info: Barcode) -> Self {
+		Self::Barcode(info)
+	}
+}
+
+impl From<Iso14443biClass> for TargetInfo {
+	fn from(info: Iso14443biClass) -> Self {
+		Self::Iso14443biClass(info)
+	}
+}
+
+// Slice accessors, returning the used part of the fixed-size arrays
+
+impl Iso14443a {
+	pub fn builder() -> Iso14443aBuilder {
+		Iso14443aBuilder::default()
+	}
+
+	pub fn uid(&self) -> &[u8] {
+		used(&self.uid, self.uid_len)
+	}
+
+	pub fn ats(&self) -> &[u8] {
+		used(&self.ats, self.ats_len)
+	}
+}
+
+impl Iso14443bi {
+	pub fn builder() -> Iso14443biBuilder {
+		Iso14443biBuilder::default()
+	}
+
+	pub fn atr(&self) -> &[u8] {
+		used(&self.atr, self.atr_len)
+	}
+}
+
+impl Dep {
+	pub fn builder() -> DepBuilder {
+		DepBuilder::default()
+	}
+
+	pub fn gb(&self) -> &[u8] {
+		used(&self.gb, self.gb_len)
+	}
+}
+
+impl Barcode {
+	pub fn builder() -> BarcodeBuilder {
+		BarcodeBuilder::default()
+	}
+
+	pub fn data(&self) -> &[u8] {
+		used(&self.data, self.data_len)
+	}
+}
+
+/// Error returned by the target info builders
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum BuildError {
+	/// A required field was not set
+	Missing(&'static str),
+	/// A field is longer than its fixed-size array
+	TooLong { field: &'static str, len: usize, max: usize },
+	/// The UID is not 4, 7 or 10 bytes long
+	InvalidUidLength(usize),
+	/// The UID size in the ATQA does not match the length of the UID
+	AtqaUidSizeMismatch,
+	/// The SAK has the cascade bit set, which means the UID is not complete
+	SakCascadeBit,
+	/// An ATS was given, but the SAK does not indicate ISO/IEC 14443-4 compliance
+	SakNotIso14443_4,
+	/// The ATS is shorter than the interface bytes indicated by its format byte
+	AtsTruncated,
+	/// A field is larger than the largest value it can have
+	OutOfRange { field: &'static str, value: u8, max: u8 },
+}
+
+impl fmt::Display for BuildError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			BuildError::Missing(field) => write!(f, "Missing {}", field),
+			BuildError::TooLong{ field, len, max } => write!(f, "{} is {} bytes long, at most {} bytes are supported", field, len, max),
+			BuildError::InvalidUidLength(len) => write!(f, "UID is {} bytes long, must be 4, 7 or 10 bytes", len),
+			BuildError::AtqaUidSizeMismatch => write!(f, "UID size in ATQA does not match UID length"),
+			BuildError::SakCascadeBit => write!(f, "SAK has the cascade bit set for a complete UID"),
+			BuildError::SakNotIso14443_4 => write!(f, "ATS given, but SAK does not indicate ISO/IEC 14443-4 compliance"),
+			BuildError::AtsTruncated => write!(f, "ATS is shorter than indicated by its format byte"),
+			BuildError::OutOfRange{ field, value, max } => write!(f, "{} is {}, at most {} is supported", field, value, max),
+		}
+	}
+}
+
+impl std::error::Error for BuildError {}
+
+fn to_array<const N: usize>(field: &'static str, bytes: &[u8]) -> Result<([u8; N], usize), BuildError> {
+	if bytes.len() > N {
+		return Err(BuildError::TooLong{ field, len: bytes.len(), max: N });
+	}
+	let mut array = [0u8; N];
+	array[..bytes.len()].copy_from_slice(bytes);
+	Ok((array, bytes.len()))
+}
+
+/// Builder for `Iso14443a`.
+///
+/// The ATQA defaults to the UID size and bit frame anticollision, and the SAK
+/// to ISO/IEC 14443-4 compliance if an ATS is given. The ATS is given without
+/// its length byte (TL), like libnfc stores it.
+#[derive(Default, Clone)]
+pub struct Iso14443aBuilder {
+	atqa: Option<[u8; 2]>,
+	sak: Option<u8>,
+	uid: Option<Vec<u8>>,
+	ats: Vec<u8>,
+}
+
+impl Iso14443aBuilder {
+	pub fn atqa(mut self, atqa: [u8; 2]) -> Self {
+		self.atqa = Some(atqa);
+		self
+	}
+
+	pub fn sak(mut self, sak: u8) -> Self {
+		self.sak = Some(sak);
+		self
+	}
+
+	pub fn uid(mut self, uid: &[u8]) -> Self {
+		self.uid = Some(uid.to_vec());
+		self
+	}
+
+	pub fn ats(mut self, ats: &[u8]) -> Self {
+		self.ats = ats.to_vec();
+		self
+	}
+
+	pub fn build(self) -> Result<Iso14443a, BuildError> {
+		let uid = self.uid.ok_or(BuildError::Missing("uid"))?;
+		let size_bits = match uid.len() {
+			4 => 0,
+			7 => 1,
+			10 => 2,
+			len => return Err(BuildError::InvalidUidLength(len)),
+		};
+		let atqa = self.atqa.unwrap_or([0x00, size_bits << 6 | 0x04]);
+		if atqa[1] >> 6 != size_bits {
+			return Err(BuildError::AtqaUidSizeMismatch);
+		}
+		let sak = self.sak.unwrap_or(if self.ats.is_empty() { 0x00 } else { 0x20 });
+		if sak & 0x04 != 0 {
+			return Err(BuildError::SakCascadeBit);
+		}
+		if let Some(&t0) = self.ats.first() {
+			if sak & 0x20 == 0 {
+				return Err(BuildError::SakNotIso14443_4);
+			}
+			if self.ats.len() < 1 + (t0 & 0x70).count_ones() as usize {
+				return Err(BuildError::AtsTruncated);
+			}
+		}
+
+		let (uid, uid_len) = to_array("uid", &uid)?;
+		let (ats, ats_len) = to_array("ats", &self.ats)?;
+		Ok(Iso14443a{ atqa, sak, uid, uid_len, ats, ats_len })
+	}
+}
+
+/// Builder for `Iso14443bi`
+#[derive(Default, Clone)]
+pub struct Iso14443biBuilder {
+	div: Option<[u8; 4]>,
+	ver_log: u8,
+	config: u8,
+	atr: Vec<u8>,
+}
+
+impl Iso14443biBuilder {
+	#[allow(clippy::should_implement_trait)]
+	pub fn div(mut self, div: [u8; 4]) -> Self {
+		self.div = Some(div);
+		self
+	}
+
+	pub fn ver_log(mut self, ver_log: u8) -> Self {
+		self.ver_log = ver_log;
+		self
+	}
+
+	pub fn config(mut self, config: u8) -> Self {
+		self.config = config;
+		self
+	}
+
+	pub fn atr(mut self, atr: &[u8]) -> Self {
+		self.atr = atr.to_vec();
+		self
+	}
+
+	pub fn build(self) -> Result<Iso14443bi, BuildError> {
+		let div = self.div.ok_or(BuildError::Missing("div"))?;
+		let (atr, atr_len) = to_array("atr", &self.atr)?;
+		Ok(Iso14443bi{ div, ver_log: self.ver_log, config: self.config, atr, atr_len })
+	}
+}
+
+/// Builder for `Dep`
+#[derive(Default, Clone)]
+pub struct DepBuilder {
+	nfcid3: Option<[u8; 10]>,
+	did: u8,
+	bs: u8,
+	br: u8,
+	to: u8,
+	pp: u8,
+	gb: Vec<u8>,
+	dep_mode: Option<DepMode>,
+}
+
+impl DepBuilder {
+	pub fn nfcid3(mut self, nfcid3: [u8; 10]) -> Self {
+		self.nfcid3 = Some(nfcid3);
+		self
+	}
+
+	pub fn did(mut self, did: u8) -> Self {
+		self.did = did;
+		self
+	}
+
+	pub fn bs(mut self, bs: u8) -> Self {
+		self.bs = bs;
+		self
+	}
+
+	pub fn br(mut self, br: u8) -> Self {
+		self.br = br;
+		self
+	}
+
+	pub fn to(mut self, to: u8) -> Self {
+		self.to = to;
+		self
+	}
+
+	pub fn pp(mut self, pp: u8) -> Self {
+		self.pp = pp;
+		self
+	}
+
+	pub fn gb(mut self, gb: &[u8]) -> Self {
+		self.gb = gb.to_vec();
+		self
+	}
+
+	pub fn dep_mode(mut self, dep_mode: DepMode) -> Self {
+		self.dep_mode = Some(dep_mode);
+		self
+	}
+
+	pub fn build(self) -> Result<Dep, BuildError> {
+		let nfcid3 = self.nfcid3.ok_or(BuildError::Missing("nfcid3"))?;
+		let (gb, gb_len) = to_array("gb", &self.gb)?;
+		Ok(Dep{
+			nfcid3,
+			did: self.did,
+			bs: self.bs,
+			br: self.br,
+			to: self.to,
+			pp: self.pp,
+			gb,
+			gb_len,
+			dep_mode: self.dep_mode.unwrap_or(DepMode::Undefined),
+		})
+	}
+}
+
+/// Builder for `Barcode`
+#[derive(Default, Clone)]
+pub struct BarcodeBuilder {
+	data: Vec<u8>,
+}
+
+impl BarcodeBuilder {
+	pub fn data(mut self, data: &[u8]) -> Self {
+		self.data = data.to_vec();
+		self
+	}
+
+	pub fn build(self) -> Result<Barcode, BuildError> {
+		let (data, data_len) = to_array("data", &self.data)?;
+		Ok(Barcode{ data, data_len })
+	}
+}
@@ -1,6 +1,8 @@
+#[cfg(feature = "libnfc")]
 use std::thread;
 use crate::*;
 
+#[cfg(feature = "libnfc")]
 #[test]
 fn context_new_drop() {
 	let context = std::hint::black_box(Context::new());
@@ -8,6 +10,7 @@ fn context_new_drop() {
 	std::mem::drop(context);
 }
 
+#[cfg(feature = "libnfc")]
 #[test]
 fn parallel_context_new_drop() {
 	let thread_count = thread::available_parallelism();
@@ -24,4 +27,796 @@ fn parallel_context_new_drop() {
     for handle in handles {
 		assert!(handle.join().is_ok());
 	}
-}
\ No newline at end of file
+}
+#[test]
+fn config_parse_roundtrip() {
+	let input = "# comment\nallow_autoscan = false\nlog_level = 3\ndevice.name = \"microBuilder.eu\"\ndevice.connstring = \"pn532_uart:/dev/ttyUSB0\"\ndevice.name = second\ndevice.connstring = pn53x_usb:\ndevice.optional = true\n";
+	let config = config::Config::parse(input);
+	assert!(config.is_ok());
+	let config = config.unwrap();
+	assert!(!config.allow_autoscan);
+	assert_eq!(config.log_level, 3);
+	assert_eq!(config.devices, vec![
+		config::DeviceConfig::new("microBuilder.eu", "pn532_uart:/dev/ttyUSB0"),
+		config::DeviceConfig{ name: "second".to_string(), connstring: "pn53x_usb:".to_string(), optional: true },
+	]);
+	assert!(config.validate().is_ok());
+	assert_eq!(config::Config::parse(&config.to_string()), Ok(config));
+}
+
+#[test]
+fn config_parse_errors() {
+	let err = config::Config::parse("allow_autoscan = true\nallow_autoscan = maybe\n").unwrap_err();
+	assert_eq!(err.line, Some(2));
+	assert_eq!(err.kind, config::ConfigErrorKind::InvalidBoolean("maybe".to_string()));
+
+	let err = config::Config::parse("device.connstring = a b\n").unwrap_err();
+	assert_eq!(err.kind, config::ConfigErrorKind::Syntax);
+
+	let too_many = (0..5).map(|i| format!("device.connstring = pn532_uart:/dev/ttyUSB{}\n", i)).collect::<String>();
+	let err = config::Config::parse(&too_many).unwrap_err();
+	assert_eq!((err.line, err.kind), (Some(5), config::ConfigErrorKind::TooManyDevices));
+
+	let device = config::DeviceConfig::parse("connstring = \"pn532_uart:/dev/ttyS0\"\n");
+	assert_eq!(device.unwrap_err().kind, config::ConfigErrorKind::MissingName(0));
+
+	// The I/O error is kept as the source
+	let dir = std::env::temp_dir().join(format!("nfc1-config-{}", std::process::id()));
+	std::fs::create_dir_all(dir.join("libnfc.conf")).unwrap();
+	let err = config::Config::load(&dir).unwrap_err();
+	std::fs::remove_dir_all(&dir).unwrap();
+	assert_eq!(err.path, Some(dir.join("libnfc.conf")));
+	assert!(matches!(err.kind, config::ConfigErrorKind::Io(_)));
+	assert!(std::error::Error::source(&err).is_some());
+}
+
+#[test]
+fn iso14443_crc() {
+	assert_eq!(iso14443a_crc(&mut [0x00, 0x00]), vec![0xa0, 0x1e]);
+	assert_eq!(iso14443a_crc(&mut [0x12, 0x34]), vec![0x26, 0xcf]);
+	assert_eq!(iso14443b_crc(&mut [0x00, 0x00, 0x00]), vec![0xcc, 0xc6]);
+	assert_eq!(iso14443b_crc(&mut [0x0f, 0xaa, 0xff]), vec![0xfc, 0xd1]);
+
+	let mut hlta = vec![0x50, 0x00];
+	iso14443a_crc_append(&mut hlta);
+	assert_eq!(hlta, vec![0x50, 0x00, 0x57, 0xcd]);
+}
+
+#[test]
+fn crc_and_parity() {
+	const HLTA_CRC: [u8; 2] = crc::crc_a(&[0x50, 0x00]);
+	assert_eq!(HLTA_CRC, [0x57, 0xcd]);
+	assert_eq!(crc::crc_b(&[0x0f, 0xaa, 0xff]), [0xfc, 0xd1]);
+
+	let mut reqb = vec![0x05, 0x00, 0x00];
+	crc::append_crc_b(&mut reqb);
+	assert!(crc::check_crc_b(&reqb));
+	assert_eq!(crc::strip_crc_b(&reqb), Some(&[0x05, 0x00, 0x00][..]));
+	assert!(!crc::check_crc_a(&reqb));
+	assert_eq!(crc::strip_crc_a(&[0x50, 0x00, 0x57, 0xce]), None);
+	assert!(!crc::check_crc_a(&[0x57]));
+
+	assert_eq!(crc::odd_parity(0x00), 1);
+	assert_eq!(crc::odd_parity(0x93), 1);
+	assert_eq!(crc::odd_parity(0x26), 0);
+	let data = [0x93, 0x20, 0x50];
+	let parity = crc::parity(&data);
+	assert_eq!(parity, vec![1, 0, 1]);
+	let mut filled = [0; 3];
+	crc::fill_parity(&data, &mut filled);
+	assert_eq!(filled, [1, 0, 1]);
+	assert!(crc::check_parity(&data, &parity));
+	assert_eq!(crc::find_parity_error(&data, &[1, 1, 1]), Some(1));
+	assert!(!crc::check_parity(&data, &parity[..2]));
+}
+
+#[test]
+fn raw_frames() {
+	use raw::{CascadeLevel, RawResponse};
+
+	let reqa = RawFrame::short(0x26);
+	assert_eq!((reqa.data(), reqa.bits()), (&[0x26][..], 7));
+
+	let hlta = RawFrame::standard(&[0x50, 0x00]);
+	assert_eq!((hlta.data(), hlta.bits(), hlta.parity()), (&[0x50, 0x00, 0x57, 0xcd][..], 32, &[1, 1, 0, 0][..]));
+
+	let anticoll = RawFrame::anticollision(CascadeLevel::One, &[], 0).unwrap();
+	assert_eq!((anticoll.data(), anticoll.bits()), (&[0x93, 0x20][..], 16));
+	let partial = RawFrame::anticollision(CascadeLevel::Two, &[0x04, 0xa1], 12).unwrap();
+	assert_eq!((partial.data(), partial.bits()), (&[0x95, 0x34, 0x04, 0x01][..], 28));
+	assert_eq!(RawFrame::anticollision(CascadeLevel::One, &[0x04], 9), Err(Error::InvalidArgument));
+
+	let select = RawFrame::select(CascadeLevel::One, &[0x04, 0xa1, 0xb2, 0xc3]);
+	assert_eq!(&select.data()[..7], &[0x93, 0x70, 0x04, 0xa1, 0xb2, 0xc3, 0xd4]);
+	assert!(crc::check_crc_a(select.data()));
+	assert_eq!(CascadeLevel::from_sel(0x97), Some(CascadeLevel::Three));
+	assert_eq!(CascadeLevel::Three.next(), None);
+
+	let sak = RawResponse{ data: vec![0x08, 0xb6, 0xdd], bits: 24, parity: Some(vec![1, 0, 1]) };
+	assert!(sak.crc_valid());
+	assert_eq!(sak.payload(), Some(&[0x08][..]));
+	assert!(!sak.parity_valid());
+	let atqa = RawResponse{ data: vec![0x04, 0x00], bits: 16, parity: None };
+	assert_eq!(atqa.payload(), None);
+	assert!(atqa.parity_valid());
+}
+
+#[test]
+fn anticollision_inventory() {
+	use raw::RawResponse;
+
+	#[derive(PartialEq)]
+	enum State { Idle, Ready(usize), Active, Halt }
+	struct Card { uid: Vec<u8>, atqa: [u8; 2], sak: u8, state: State }
+	impl Card {
+		fn cln(&self, level: usize) -> Vec<u8> {
+			let complete = self.uid.len() / 3 - 1 == level;
+			let start = level * 3;
+			if complete { self.uid[start..].to_vec() } else { [&[0x88], &self.uid[start..start + 3]].concat() }
+		}
+	}
+	let bit = |data: &[u8], i: usize| (data[i / 8] >> (i % 8)) & 1;
+	// Answers of every card combined, with parity errors in the bytes where they differ. Like
+	// PN53x devices, silence is reported as an RF transmission error.
+	let answer = |answers: Vec<(Vec<u8>, usize)>| {
+		let Some(bits) = answers.iter().map(|(_, bits)| *bits).max() else { return Err(Error::RfTransmissionError) };
+		let mut data = vec![0u8; bits.div_ceil(8)];
+		for (answer, _) in &answers {
+			answer.iter().enumerate().for_each(|(i, byte)| data[i] |= byte);
+		}
+		let mut parity = crc::parity(&data);
+		for (i, parity) in parity.iter_mut().enumerate() {
+			if answers.iter().any(|(answer, _)| answer.get(i) != Some(&data[i])) {
+				*parity ^= 1;
+			}
+		}
+		Ok(RawResponse{ data, bits, parity: Some(parity) })
+	};
+
+	let mut field: Vec<Card> = [
+		(vec![0x04, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66], [0x44, 0x00], 0x00),
+		(vec![0x04, 0x11, 0x22, 0x33, 0x44, 0x55, 0x67], [0x44, 0x00], 0x00),
+		(vec![0xde, 0xad, 0xbe, 0xef], [0x04, 0x00], 0x08),
+		(vec![0x08, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09], [0x84, 0x00], 0x20),
+	].into_iter().map(|(uid, atqa, sak)| Card{ uid, atqa, sak, state: State::Idle }).collect();
+	// The first card is still active from an earlier selection
+	field[0].state = State::Active;
+
+	let result = inventory::inventory(|frame| {
+		let data = frame.data();
+		if frame.bits() == 7 {
+			let wake: Vec<&mut Card> = field.iter_mut().filter(|card| card.state == State::Idle || (data[0] == 0x52 && card.state == State::Halt)).collect();
+			let answers = wake.iter().map(|card| (card.atqa.to_vec(), 16)).collect();
+			wake.into_iter().for_each(|card| card.state = State::Ready(0));
+			return answer(answers);
+		}
+		if data[..2] == [0x50, 0x00] {
+			field.iter_mut().filter(|card| card.state == State::Active).for_each(|card| card.state = State::Halt);
+			return Err(Error::RfTransmissionError);
+		}
+		let level = (data[0] as usize - 0x93) / 2;
+		let ready = |card: &&mut Card| card.state == State::Ready(level);
+		if data[1] == 0x70 {
+			let mut answers = vec![];
+			for card in field.iter_mut().filter(ready) {
+				if card.cln(level) != data[2..6] {
+					card.state = State::Idle;
+					continue;
+				}
+				let complete = card.uid.len() / 3 - 1 == level;
+				let mut sak = vec![if complete { card.sak } else { 0x04 }];
+				crc::append_crc_a(&mut sak);
+				answers.push((sak, 24));
+				card.state = if complete { State::Active } else { State::Ready(level + 1) };
+			}
+			return answer(answers);
+		}
+		let known_bits = frame.bits() - 16;
+		let answers = field.iter_mut().filter(ready).filter_map(|card| {
+			let mut cln = card.cln(level);
+			cln.push(cln.iter().fold(0, |bcc, byte| bcc ^ byte));
+			if (0..known_bits).any(|i| bit(&cln, i) != bit(&data[2..], i)) {
+				return None;
+			}
+			let mut rest = vec![0u8; (40 - known_bits).div_ceil(8)];
+			for i in known_bits..40 {
+				rest[(i - known_bits) / 8] |= bit(&cln, i) << ((i - known_bits) % 8);
+			}
+			Some((rest, 40 - known_bits))
+		}).collect();
+		answer(answers)
+	}).unwrap();
+	assert!(result.complete);
+	let cards = result.cards;
+
+	let mut uids: Vec<&[u8]> = cards.iter().map(|card| &card.uid[..]).collect();
+	uids.sort();
+	assert_eq!(uids, vec![
+		&[0x04, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66][..],
+		&[0x04, 0x11, 0x22, 0x33, 0x44, 0x55, 0x67][..],
+		&[0x08, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09][..],
+		&[0xde, 0xad, 0xbe, 0xef][..],
+	]);
+	let last = cards.last().unwrap();
+	assert_eq!((last.atqa, last.sak), (Some([0x04, 0x00]), 0x08));
+	assert_eq!(cards.iter().find(|card| card.uid.len() == 10).unwrap().uid_size(), Some(UidSize::Triple));
+	assert!(!format!("{:?}", cards[0]).contains("11"));
+
+	// A field answering every frame with noise ends in a bounded number of frames, and is reported as incomplete
+	let mut frames = 0;
+	let result = inventory::inventory(|frame| {
+		frames += 1;
+		Ok(RawResponse{ data: vec![0xff; 5], bits: 40 - frame.bits().saturating_sub(16).min(32), parity: Some(vec![0; 5]) })
+	}).unwrap();
+	assert!(result.cards.is_empty() && !result.complete);
+	assert!(frames < 1000, "{} frames", frames);
+}
+
+#[test]
+fn ats_parse_build() {
+	// MIFARE DESFire EV1
+	let ats = Ats::parse(&[0x75, 0x77, 0x81, 0x02, 0x80]).unwrap();
+	assert_eq!((ats.fsci, ats.fsc()), (5, 64));
+	assert!(!ats.same_bit_rate_required());
+	assert_eq!(ats.picc_to_pcd_bit_rates(), vec![BaudRate::Baud106, BaudRate::Baud212, BaudRate::Baud424, BaudRate::Baud847]);
+	assert_eq!((ats.fwi(), ats.fwt().as_micros()), (8, 77328));
+	assert_eq!((ats.sfgi(), ats.sfgt().map(|sfgt| sfgt.as_micros())), (1, Some(604)));
+	assert!(!ats.nad_supported() && ats.cid_supported());
+	assert_eq!(ats.historical_bytes, vec![0x80]);
+	assert_eq!(Ats::parse_with_length(&[0x06, 0x75, 0x77, 0x81, 0x02, 0x80]), Ok(ats.clone()));
+
+	// Absent interface bytes have their default values
+	let minimal = Ats::parse(&[0x08]).unwrap();
+	assert_eq!((minimal.fsc(), minimal.fwi(), minimal.sfgt()), (256, 4, None));
+	assert_eq!(minimal.pcd_to_picc_bit_rates(), vec![BaudRate::Baud106]);
+	assert!(minimal.cid_supported());
+
+	assert_eq!(Ats::parse(&[0x78, 0x80]), Err(ParseError::Truncated{ expected: 4, len: 2 }));
+	assert_eq!(Ats::parse(&[0x88]), Err(ParseError::InvalidValue{ offset: 0, value: 0x88 }));
+	assert_eq!(Ats::parse(&[]), Err(ParseError::Truncated{ expected: 1, len: 0 }));
+
+	let built = Ats::builder()
+		.fsci(5)
+		.bit_rates(&[BaudRate::Baud212, BaudRate::Baud424, BaudRate::Baud847], &[BaudRate::Baud212, BaudRate::Baud424, BaudRate::Baud847])
+		.fwi(8)
+		.sfgi(1)
+		.cid_supported(true)
+		.historical_bytes(&[0x80])
+		.build()
+		.unwrap();
+	assert_eq!(built, ats);
+	assert_eq!(built.to_bytes(), vec![0x75, 0x77, 0x81, 0x02, 0x80]);
+	assert_eq!(Ats::builder().build().unwrap().to_bytes(), vec![0x08]);
+	assert_eq!(Ats::builder().fwi(15).build(), Err(target_info::BuildError::OutOfRange{ field: "FWI", value: 15, max: 14 }));
+}
+
+#[test]
+fn historical_bytes() {
+	use historical::{DataObject, Fingerprint, HistoricalBytes, StatusIndicator};
+
+	let jcop = HistoricalBytes::parse(&[0x80, 0x31, 0x80, 0x66, 0xb0, 0x84, 0x0c, 0x01, 0x6e, 0x01, 0x83, 0x00, 0x90, 0x00]).unwrap();
+	assert_eq!(jcop.card_service_data(), Some(0x80));
+	assert_eq!(jcop.pre_issuing_data(), Some(&[0xb0, 0x84, 0x0c, 0x01, 0x6e, 0x01][..]));
+	assert_eq!(jcop.status, Some(StatusIndicator{ lcs: Some(0x00), sw: Some([0x90, 0x00]) }));
+	assert_eq!(jcop.fingerprint(), None);
+
+	let mandatory_status = HistoricalBytes::parse(&[0x00, 0x31, 0xc0, 0x73, 0x80, 0x01, 0x00, 0x00, 0x90, 0x00]).unwrap();
+	assert_eq!(mandatory_status.objects, vec![DataObject::CardServiceData(0xc0), DataObject::CardCapabilities(vec![0x80, 0x01, 0x00])]);
+	assert_eq!(mandatory_status.status.unwrap().sw, Some([0x90, 0x00]));
+
+	let pcsc = HistoricalBytes::parse(&[0x80, 0x4f, 0x0c, 0xa0, 0x00, 0x00, 0x03, 0x06, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00]).unwrap();
+	assert_eq!(pcsc.fingerprint(), Some(Fingerprint::PcscStorageCard{ standard: 0x03, card_name: 0x0001 }));
+	assert_eq!(pcsc.fingerprint().unwrap().to_string(), "MIFARE Classic 1K");
+
+	let desfire = HistoricalBytes::parse(&[0xc1, 0x05, 0x13, 0x21, 0x30, 0x00, 0x00]).unwrap();
+	assert_eq!(desfire.fingerprint().unwrap().to_string(), "MIFARE DESFire 4K");
+	let jcop = HistoricalBytes::parse(b"JCOP41V221").unwrap();
+	assert_eq!(jcop.fingerprint(), Some(Fingerprint::Jcop("JCOP41V221".to_string())));
+
+	assert_eq!(HistoricalBytes::parse(&[0x80, 0x65, 0x01]), Err(ParseError::Truncated{ expected: 7, len: 3 }));
+	assert_eq!(HistoricalBytes::parse(&[0x00, 0x90]), Err(ParseError::Truncated{ expected: 4, len: 2 }));
+}
+
+#[test]
+fn atqb_protocol_info() {
+	use atqb::ProtocolInfo;
+
+	let info = target_info::Iso14443b{ pupi: [0x12, 0x34, 0x56, 0x78], application_data: [0x00; 4], protocol_info: [0x11, 0x81, 0x85], card_identifier: 0 };
+	let protocol_info = info.decode_protocol_info();
+	assert_eq!(protocol_info.picc_to_pcd_bit_rates(), vec![BaudRate::Baud106, BaudRate::Baud212]);
+	assert_eq!(protocol_info.pcd_to_picc_bit_rates(), vec![BaudRate::Baud106, BaudRate::Baud212]);
+	assert!(!protocol_info.same_bit_rate_required());
+	assert_eq!(protocol_info.fsc(), 256);
+	assert!(protocol_info.iso14443_4_compliant());
+	assert_eq!((protocol_info.fwi, protocol_info.fwt().as_micros()), (8, 77328));
+	assert!(protocol_info.application_data_coded());
+	assert!(protocol_info.cid_supported() && !protocol_info.nad_supported());
+	assert_eq!(protocol_info.sfgt(), None);
+
+	let extended = ProtocolInfo::parse(&[0x11, 0x81, 0x85, 0x10]).unwrap();
+	assert_eq!(extended.sfgt().map(|sfgt| sfgt.as_micros()), Some(604));
+	assert_eq!(ProtocolInfo::parse(&[0x11, 0x81]), Err(ParseError::Truncated{ expected: 3, len: 2 }));
+}
+
+#[test]
+fn atr_parse_build() {
+	// Pseudo-ATR of a MIFARE Classic 1K in a PC/SC reader
+	let bytes = [0x3b, 0x8f, 0x80, 0x01, 0x80, 0x4f, 0x0c, 0xa0, 0x00, 0x00, 0x03, 0x06, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x6a];
+	let atr = Atr::parse(&bytes).unwrap();
+	assert!(!atr.inverse_convention());
+	assert_eq!(atr.protocols(), vec![0, 1]);
+	assert_eq!((atr.td(1), atr.td(2), atr.ta(1)), (Some(0x80), Some(0x01), None));
+	assert_eq!((atr.fi(), atr.di(), atr.ifsc()), (Some(372), Some(1), 32));
+	assert_eq!(atr.tck, Some(0x6a));
+	let historical = atr.historical().unwrap();
+	assert_eq!(historical.fingerprint(), Some(historical::Fingerprint::PcscStorageCard{ standard: 0x03, card_name: 0x0001 }));
+
+	let built = Atr::builder().protocol(0).protocol(1).historical_bytes(&bytes[4..19]).build().unwrap();
+	assert_eq!(built, atr);
+	assert_eq!(built.to_bytes(), bytes);
+
+	let mut corrupted = bytes;
+	corrupted[19] ^= 0x01;
+	assert_eq!(Atr::parse(&corrupted), Err(ParseError::InvalidValue{ offset: 19, value: 0x6b }));
+	assert_eq!(Atr::parse(&bytes[..10]), Err(ParseError::Truncated{ expected: 19, len: 10 }));
+	// T=0 only: no TCK
+	let t0 = Atr::builder().ta(1, 0x96).tc(1, 0x02).build().unwrap();
+	assert_eq!(t0.to_bytes(), vec![0x3b, 0x50, 0x96, 0x02]);
+	assert_eq!((t0.fi(), t0.di(), t0.extra_guard_time()), (Some(512), Some(32), 2));
+	assert_eq!(Atr::builder().tb(3, 0x45).protocol(1).build(), Err(target_info::BuildError::Missing("protocol")));
+
+	let info = target_info::Iso14443bi::builder().div([0x01, 0x02, 0x03, 0x04]).atr(&bytes).build().unwrap();
+	assert_eq!(info.decode_atr(), Ok(Some(atr)));
+}
+
+#[test]
+fn pcsc_atr() {
+	use target_info::{Iso14443a, TargetInfo};
+
+	let uid = [0x04, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf6];
+	let classic = TargetInfo::Iso14443a(Iso14443a::builder().uid(&uid[..4]).sak(0x08).build().unwrap());
+	assert_eq!(classic.pcsc_atr().unwrap().to_bytes(), vec![0x3b, 0x8f, 0x80, 0x01, 0x80, 0x4f, 0x0c, 0xa0, 0x00, 0x00, 0x03, 0x06, 0x03, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x6a]);
+	let desfire = Target{ target_info: TargetInfo::Iso14443a(Iso14443a::builder().uid(&uid).ats(&[0x75, 0x77, 0x81, 0x02, 0x80]).build().unwrap()), modulation: Modulation{ modulation_type: ModulationType::Iso14443a, baud_rate: BaudRate::Baud106 } };
+	assert_eq!(desfire.pcsc_atr().unwrap().to_bytes(), vec![0x3b, 0x81, 0x80, 0x01, 0x80, 0x80]);
+	let mut truncated = TargetInfo::new_iso14443a();
+	if let TargetInfo::Iso14443a(info) = &mut truncated {
+		info.ats[..2].copy_from_slice(&[0x75, 0x77]);
+		info.ats_len = 2;
+	}
+	assert_eq!(truncated.pcsc_atr(), None);
+
+	let type_b = TargetInfo::Iso14443b(target_info::Iso14443b{ pupi: [0x12, 0x34, 0x56, 0x78], application_data: [0x00, 0x00, 0x00, 0x00], protocol_info: [0x11, 0x81, 0x85], card_identifier: 0 });
+	let atr = type_b.pcsc_atr().unwrap();
+	assert_eq!(atr.historical_bytes, vec![0x00, 0x00, 0x00, 0x00, 0x11, 0x81, 0x85, 0x00]);
+	assert_eq!(Atr::parse(&atr.to_bytes()), Ok(atr));
+
+	let felica = TargetInfo::new_felica().pcsc_atr().unwrap();
+	assert_eq!(felica.historical().unwrap().fingerprint().unwrap().to_string(), "FeliCa");
+	assert_eq!(TargetInfo::new_dep().pcsc_atr(), None);
+}
+
+#[test]
+fn card_identification() {
+	use identify::*;
+	use target_info::Iso14443a;
+
+	let uid = [0x04, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf6];
+	let card = |atqa: [u8; 2], sak: u8, ats: &[u8]| Iso14443a{ atqa, ..Iso14443a::builder().uid(&uid).sak(sak).ats(ats).build().unwrap() };
+	assert_eq!(identify(&card([0x00, 0x04], 0x08, &[])), CardType::MifareClassic1k);
+	assert_eq!(identify(&card([0x00, 0x02], 0x18, &[])), CardType::MifareClassic4k);
+	assert_eq!(identify(&card([0x00, 0x44], 0x18, &[])), CardType::MifarePlus4kSl1);
+	assert_eq!(identify(&card([0x00, 0x44], 0x00, &[])), CardType::MifareUltralight);
+	assert_eq!(identify(&card([0x03, 0x44], 0x20, &[0x75, 0x77, 0x81, 0x02, 0x80])), CardType::MifareDesfire);
+	assert_eq!(identify(&card([0x03, 0x44], 0x20, &[0x78, 0x77, 0x71, 0x02, 0xc1, 0x05, 0x13, 0x21, 0x30, 0x00, 0x00])), CardType::MifareDesfireEv2);
+	assert_eq!(identify(&card([0x00, 0x04], 0x28, &[0x38, 0x77, 0xb1, 0x4a, 0x43, 0x4f, 0x50, 0x34, 0x31, 0x56, 0x32, 0x32, 0x31])).to_string(), "NXP JCOP41V221");
+
+	// NTAG215 answers GET_VERSION
+	let ntag = refine(CardType::MifareUltralight, |command| match command {
+		[0x60] => Ok(vec![0x00, 0x04, 0x04, 0x02, 0x01, 0x00, 0x11, 0x03]),
+		_ => Err(Error::Timeout),
+	}).unwrap();
+	assert_eq!(ntag.to_string(), "NTAG215");
+	// Ultralight C does not, but starts the authentication
+	let mut commands = vec![];
+	let ultralight_c = refine(CardType::MifareUltralight, |command| {
+		commands.push(command.to_vec());
+		match command {
+			[0x1a, 0x00] => Ok(vec![0xaf, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]),
+			_ => Err(Error::RfTransmissionError),
+		}
+	}).unwrap();
+	assert_eq!(ultralight_c, CardType::MifareUltralightC);
+	assert_eq!(commands, vec![vec![0x60], vec![0x1a, 0x00]]);
+	assert_eq!(refine(CardType::MifareUltralight, |_| Err(Error::Timeout)), Ok(CardType::MifareUltralight));
+	assert_eq!(refine(CardType::MifareUltralight, |_| Err(Error::Io)), Err(Error::Io));
+
+	assert_eq!(from_version(&[0xaf, 0x04, 0x01, 0x01, 0x33, 0x00, 0x1a, 0x05]), Some(CardType::MifareDesfireEv3));
+	assert_eq!(from_version(&[0xaf, 0x04, 0x04, 0x02, 0x30, 0x00, 0x11, 0x05]), Some(CardType::Ntag424Dna));
+	assert_eq!(refine(CardType::MifareClassic1k, |_| unreachable!()), Ok(CardType::MifareClassic1k));
+}
+
+#[cfg(all(feature = "log", unix))]
+#[test]
+fn logging_parse() {
+	use logging::*;
+
+	let level = LogLevel::new(LogPriority::Error).with_group(LogGroup::Chip, LogPriority::Debug);
+	assert_eq!(u32::from(level), 0xc1);
+	assert_eq!(level.group(LogGroup::Chip), LogPriority::Debug);
+	assert_eq!(level.group(LogGroup::Driver), LogPriority::None);
+
+	assert_eq!(parse_line("debug\tlibnfc.chip.pn53x\tTX: 00 ff"), Some((log::Level::Debug, "libnfc.chip.pn53x", "TX: 00 ff")));
+	assert_eq!(parse_line("thread 'main' panicked"), None);
+}
+
+#[test]
+fn redaction() {
+	use redact::{Redacted, Redaction, with_policy};
+
+	let uid = [0x04, 0xa1, 0xb2, 0xc3];
+	assert_eq!(with_policy(Redaction::Mask, || Redacted(&uid).to_string()), "** ** ** **");
+	assert_eq!(with_policy(Redaction::Truncate(1), || Redacted(&uid).to_string()), "04 ** ** **");
+	assert_eq!(redact::reveal(|| Redacted(&uid).to_string()), "04 a1 b2 c3");
+
+	let hash = |salt: &[u8]| with_policy(Redaction::Hash(salt.to_vec()), || Redacted(&uid).to_string());
+	assert_eq!(hash(b"salt"), hash(b"salt"));
+	assert_ne!(hash(b"salt"), hash(b"pepper"));
+
+	let mut info = target_info::Iso14443a{ atqa: [0x00, 0x04], sak: 0x08, uid: [0; 10], uid_len: 4, ats: [0; 254], ats_len: 0 };
+	info.uid[..4].copy_from_slice(&uid);
+	let debug = with_policy(Redaction::Mask, || format!("{:?}", info));
+	assert!(debug.contains("uid: ** ** ** **,"));
+	assert!(!debug.contains("a1"));
+}
+
+#[test]
+fn redaction_siphash() {
+	// Reference vector from the SipHash paper
+	let data: Vec<u8> = (0..15).collect();
+	assert_eq!(redact::siphash(0x0706050403020100, 0x0f0e0d0c0b0a0908, &data), 0xa129ca6149be45e5);
+}
+
+#[test]
+fn trace_pcapng() {
+	use trace::{Direction, Frame, pcapng::PcapngWriter};
+	use std::time::{Duration, UNIX_EPOCH};
+
+	let mut writer = PcapngWriter::new(vec![]).unwrap();
+	let mut reqa = Frame::new(Direction::InitiatorToTarget, &[0x26]);
+	reqa.bits = Some(7);
+	reqa.timestamp = UNIX_EPOCH + Duration::from_micros(0x1_0000_0002);
+	writer.write_frame(&reqa).unwrap();
+	let mut atqa = Frame::new(Direction::TargetToInitiator, &[0x44, 0x00, 0x12, 0x34]);
+	atqa.has_crc = true;
+	writer.write_frame(&atqa).unwrap();
+	assert!(redact::with_policy(redact::Redaction::Mask, || format!("{:?}", atqa)).contains("data: ** ** ** **"));
+	let pcapng = writer.finish().unwrap();
+
+	// Section header, then interface description with LINKTYPE_ISO_14443
+	assert_eq!(&pcapng[0..4], &[0x0a, 0x0d, 0x0d, 0x0a]);
+	assert_eq!(&pcapng[8..12], &[0x4d, 0x3c, 0x2b, 0x1a]);
+	assert_eq!(&pcapng[28..32], &[0x01, 0x00, 0x00, 0x00]);
+	assert_eq!(&pcapng[36..38], &264u16.to_le_bytes());
+
+	// Enhanced packet: timestamp, lengths, pseudo-header and data
+	let epb = &pcapng[48..];
+	assert_eq!(&epb[0..4], &[0x06, 0x00, 0x00, 0x00]);
+	assert_eq!(&epb[12..20], &[0x01, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00]);
+	assert_eq!(&epb[20..24], &5u32.to_le_bytes());
+	assert_eq!(&epb[28..33], &[0x00, 0xfa, 0x00, 0x01, 0x26]);
+	let len = u32::from_le_bytes(epb[4..8].try_into().unwrap()) as usize;
+	assert_eq!(&epb[len - 4..len], &epb[4..8]);
+	assert!(String::from_utf8_lossy(&epb[..len]).contains("7 bits"));
+
+	let epb = &epb[len..];
+	assert_eq!(&epb[28..36], &[0x00, 0xff, 0x00, 0x04, 0x44, 0x00, 0x12, 0x34]);
+}
+
+#[test]
+fn trace_decode() {
+	use trace::{Direction, Frame, decode::{annotate, CrcStatus}};
+
+	let frame = |direction, data: &[u8], crc: bool| {
+		let mut data = data.to_vec();
+		if crc {
+			iso14443a_crc_append(&mut data);
+		}
+		let mut frame = Frame::new(direction, &data);
+		frame.has_crc = true;
+		frame
+	};
+	let (rdr, tag) = (Direction::InitiatorToTarget, Direction::TargetToInitiator);
+
+	let mut reqa = frame(rdr, &[0x26], false);
+	reqa.bits = Some(7);
+	let mut bad_crc = frame(tag, &[0x03, 0x90, 0x00], true);
+	bad_crc.data[3] ^= 0xff;
+	let mut mifare = frame(rdr, &[0x60, 0x04], true);
+	mifare.parity = Some(vec![0, 0, 0, 0]);
+
+	let frames = vec![
+		reqa,
+		frame(tag, &[0x44, 0x00], false),
+		frame(rdr, &[0x93, 0x20], false),
+		frame(tag, &[0x88, 0x04, 0xa1, 0xb2, 0x88 ^ 0x04 ^ 0xa1 ^ 0xb2], false),
+		frame(rdr, &[0x93, 0x70, 0x88, 0x04, 0xa1, 0xb2, 0x88 ^ 0x04 ^ 0xa1 ^ 0xb2], true),
+		frame(tag, &[0x24], true),
+		frame(rdr, &[0xe0, 0x80], true),
+		frame(tag, &[0x05, 0x78, 0x80, 0x70, 0x02], true),
+		frame(rdr, &[0x02, 0x00, 0xa4, 0x04, 0x00, 0x07, 0xd2, 0x76, 0x00, 0x00, 0x85, 0x01, 0x01, 0x00], true),
+		frame(tag, &[0x02, 0x6a, 0x82], true),
+		frame(rdr, &[0xb3], true),
+		bad_crc,
+		frame(rdr, &[0xc2], true),
+		frame(tag, &[0xc2], true),
+		mifare,
+	];
+	let annotations = annotate(&frames);
+	let descriptions: Vec<&str> = annotations.iter().map(|annotation| annotation.description.as_str()).collect();
+	assert_eq!(descriptions, vec![
+		"REQA", "ATQA", "ANTICOLL", "UID, BCC ok", "SELECT_UID", "SAK (UID not complete)", "RATS", "ATS",
+		"I-block(0) SELECT (by name)", "I-block(0) SW 6a82: file not found", "R-block(1) NAK", "I-block(1) SW 9000: OK",
+		"S-block DESELECT", "S-block DESELECT", "AUTH-A(4)",
+	]);
+	assert_eq!(annotations[3].crc, CrcStatus::Absent);
+	assert_eq!(annotations[4].crc, CrcStatus::Valid);
+	assert_eq!(annotations[11].crc, CrcStatus::Invalid);
+	// Bytes with an even number of ones (0x60 and the first CRC byte) need a parity bit of 1
+	assert_eq!(annotations[14].parity_errors, vec![0, 2]);
+}
+
+#[test]
+fn trace_import() {
+	use std::time::{Duration, UNIX_EPOCH};
+	use trace::{Direction, import::{self, ImportErrorKind}};
+
+	let modulation = Modulation{ modulation_type: ModulationType::Iso14443a, baud_rate: BaudRate::Baud106 };
+
+	// REQA at 1356 carrier periods, ATQA with the response flag set, 11 bytes with two parity bytes
+	let mut pm3 = vec![];
+	pm3.extend_from_slice(&1356u32.to_le_bytes());
+	pm3.extend_from_slice(&[0x00, 0x00, 0x01, 0x00, 0x26, 0x80]);
+	pm3.extend_from_slice(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x80, 0x44, 0x00, 0x40]);
+	pm3.extend_from_slice(&[0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0b, 0x00]);
+	pm3.extend_from_slice(&[0u8; 11]);
+	pm3.extend_from_slice(&[0xff, 0x20]);
+	let frames = import::proxmark3(&pm3, modulation, UNIX_EPOCH).unwrap();
+	assert_eq!(frames.len(), 3);
+	assert_eq!(frames[0].timestamp, UNIX_EPOCH + Duration::from_micros(100));
+	assert_eq!((frames[0].direction, frames[0].bits), (Direction::InitiatorToTarget, Some(7)));
+	assert_eq!((frames[1].direction, &frames[1].data[..]), (Direction::TargetToInitiator, &[0x44, 0x00][..]));
+	assert_eq!(frames[1].parity, Some(vec![0, 1]));
+	assert_eq!(frames[2].parity, Some(vec![1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 1]));
+	let err = import::proxmark3(&pm3[..pm3.len() - 1], modulation, UNIX_EPOCH).unwrap_err();
+	assert_eq!((err.kind, err.offset), (ImportErrorKind::Truncated, Some(21)));
+
+	// libnfc logs the PN53x frames at the driver (pn53x_usb) or bus (uart, where each read is logged separately) layer
+	let log = "\
+debug\tlibnfc.chip.pn53x\tWriteRegister
+debug\tlibnfc.driver.pn53x_usb\tTX: 00 00 ff 08 f8 d4 08 63 02 00 63 3d 07 18 00 
+debug\tlibnfc.driver.pn53x_usb\tRX: 00 00 ff 00 ff 00 
+debug\tlibnfc.driver.pn53x_usb\tRX: 00 00 ff 02 fe d5 09 22 00 
+debug\tlibnfc.chip.pn53x\tInCommunicateThru
+debug\tlibnfc.driver.pn53x_usb\tTX: 00 00 ff 03 fd d4 42 26 c4 00 
+debug\tlibnfc.driver.pn53x_usb\tRX: 00 00 ff 00 ff 00 
+debug\tlibnfc.driver.pn53x_usb\tRX: 00 00 ff 05 fb d5 43 00 04 00 e4 00 
+debug\tlibnfc.chip.pn53x\tWriteRegister
+debug\tlibnfc.driver.pn53x_usb\tTX: 00 00 ff 05 fb d4 08 63 3d 00 84 00 
+debug\tlibnfc.driver.pn53x_usb\tRX: 00 00 ff 00 ff 00 
+debug\tlibnfc.driver.pn53x_usb\tRX: 00 00 ff 02 fe d5 09 22 00 
+debug\tlibnfc.chip.pn53x\tInDataExchange
+debug\tlibnfc.driver.pn53x_usb\tTX: 00 00 ff 05 fb d4 40 01 30 04 b7 00 
+debug\tlibnfc.driver.pn53x_usb\tRX: 00 00 ff 00 ff 00 
+debug\tlibnfc.driver.pn53x_usb\tRX: 00 00 ff 03 fd d5 41 01 e9 00 
+debug\tlibnfc.bus.uart\tTX: 55 55 00 00 00 00 00 00 00 00 00 00 00 00 00 00 
+debug\tlibnfc.bus.uart\tTX: 00 00 ff 03 fd d4 14 01 17 00 
+debug\tlibnfc.bus.uart\tRX: 00 00 ff 00 ff 00 
+debug\tlibnfc.bus.uart\tRX: 00 00 ff 02 fe 
+debug\tlibnfc.bus.uart\tRX: d5 15 
+debug\tlibnfc.bus.uart\tRX: 16 00 
+debug\tlibnfc.bus.uart\tTX: 00 00 ff 04 fc d4 42 93 20 37 00 
+debug\tlibnfc.bus.uart\tRX: 00 00 ff 00 ff 00 
+debug\tlibnfc.bus.uart\tRX: 00 00 ff 08 f8 
+debug\tlibnfc.bus.uart\tRX: d5 43 
+debug\tlibnfc.bus.uart\tRX: 00 88 04 a1 b2 9f 
+debug\tlibnfc.bus.uart\tRX: 6a 00 
+debug\tlibnfc.bus.uart\tTX: 00 00 ff ff ff 00 05 fb d4 40 01 30 08 b3 00 
+Sent bits:     93  20
+Received bits: 88  04! a1  b2  9f
+Sent bits:     5 (4 bits)
+";
+	let frames = import::libnfc_log(log, modulation, UNIX_EPOCH).unwrap();
+	let data: Vec<&[u8]> = frames.iter().map(|frame| &frame.data[..]).collect();
+	assert_eq!(data, vec![
+		&[0x26][..], &[0x04, 0x00], &[0x30, 0x04],
+		&[0x93, 0x20], &[0x88, 0x04, 0xa1, 0xb2, 0x9f], &[0x30, 0x08],
+		&[0x93, 0x20], &[0x88, 0x04, 0xa1, 0xb2, 0x9f], &[0x05],
+	]);
+	let bits: Vec<Option<usize>> = frames.iter().map(|frame| frame.bits).collect();
+	assert_eq!(bits, vec![Some(7), None, None, None, None, None, None, None, Some(4)]);
+	assert_eq!(frames[4].direction, Direction::TargetToInitiator);
+	assert_eq!(frames[7].parity, Some(vec![1, 1, 0, 1, 1]));
+
+	let err = import::libnfc_log("debug\tlibnfc.bus.uart\tRX: 00 00 ff 02 fe d5 15 17 00 ", modulation, UNIX_EPOCH).unwrap_err();
+	assert_eq!((err.kind, err.line), (ImportErrorKind::InvalidChecksum, Some(1)));
+	let err = import::libnfc_log("debug\tlibnfc.driver.acr122_usb\tTX: 6f 07 00 00 00 00 00 00 00 00 ff 00 00 00 02 d4 02 ", modulation, UNIX_EPOCH).unwrap_err();
+	assert_eq!(err.kind, ImportErrorKind::UnsupportedDriver("libnfc.driver.acr122_usb".to_string()));
+	let err = import::libnfc_log("debug\tlibnfc.bus.uart\tTX: 02 00 00 05 ff 00 00 00 00 03 ", modulation, UNIX_EPOCH).unwrap_err();
+	assert_eq!((err.kind, err.line), (ImportErrorKind::NoPn53xFrames, Some(1)));
+	let err = import::libnfc_log("Sent bits: 9x", modulation, UNIX_EPOCH).unwrap_err();
+	assert_eq!((err.kind, err.line), (ImportErrorKind::InvalidHex("9x".to_string()), Some(1)));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_target() {
+	let mut target = Target::new_iso14443a();
+	if let target_info::TargetInfo::Iso14443a(info) = &mut target.target_info {
+		info.atqa = [0x00, 0x44];
+		info.uid[..7].copy_from_slice(&[0x04, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf6]);
+		info.uid_len = 7;
+	}
+	let json = serde_json::to_string(&target).unwrap();
+	assert_eq!(json, r#"{"target_info":{"Iso14443a":{"atqa":"0044","sak":0,"uid":"04a1b2c3d4e5f6","ats":""}},"modulation":{"modulation_type":"Iso14443a","baud_rate":"Baud106"}}"#);
+	assert_eq!(serde_json::from_str::<Target>(&json).unwrap(), target);
+
+	let jewel = r#"{"target_info":{"Jewel":{"sens_res":"0c00","id":"0102030"}},"modulation":{"modulation_type":"Jewel","baud_rate":"Baud106"}}"#;
+	assert!(serde_json::from_str::<Target>(jewel).is_err());
+	let barcode = format!(r#"{{"Barcode":{{"data":"{}"}}}}"#, "00".repeat(33));
+	assert!(serde_json::from_str::<target_info::TargetInfo>(&barcode).is_err());
+	assert_eq!(serde_json::to_string(&Timeout::Duration(Duration::from_millis(1500))).unwrap(), r#"{"Duration":{"secs":1,"nanos":500000000}}"#);
+}
+
+#[test]
+fn target_display() {
+	let mut target = Target::new_iso14443a();
+	if let target_info::TargetInfo::Iso14443a(info) = &mut target.target_info {
+		info.atqa = [0x03, 0x44];
+		info.sak = 0x20;
+		info.uid[..7].copy_from_slice(&[0x04, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf6]);
+		info.uid_len = 7;
+		info.ats[..5].copy_from_slice(&[0x75, 0x77, 0x81, 0x02, 0x80]);
+		info.ats_len = 5;
+	}
+	assert_eq!(redact::reveal(|| format!("{}", target)), "\
+ISO/IEC 14443A (106 kbps) target:
+    ATQA (SENS_RES): 03  44  
+       UID (NFCID1): 04  a1  b2  c3  d4  e5  f6  
+      SAK (SEL_RES): 20  
+                ATS: 75  77  81  02  80  
+");
+	let masked = redact::with_policy(redact::Redaction::Mask, || format!("{}", target));
+	assert!(masked.contains("UID (NFCID1): **  **  **  **  **  **  **  \n"));
+	assert!(masked.contains("ATQA (SENS_RES): 03  44  \n"));
+	assert!(masked.contains("SAK (SEL_RES): 20  \n"));
+
+	let verbose = redact::reveal(|| format!("{:#}", target));
+	for line in [
+		"* UID size: double\n",
+		"* Compliant with ISO/IEC 14443-4\n",
+		"* Max Frame Size accepted by PICC: 64 bytes\n",
+		"  * PICC to PCD, DS=2, bitrate 212 kbits/s supported\n",
+		"* Frame Waiting Time: 77.33 ms\n",
+		"* Start-up Frame Guard Time: 0.6041 ms\n",
+		"* Card IDentifier supported\n",
+		"* Historical bytes Tk: 80  \n",
+		"  * No COMPACT-TLV objects found, no status found\n",
+		"* MIFARE DESFire / Desfire EV1\n",
+	] {
+		assert!(verbose.contains(line), "{:?} not in {}", line, verbose);
+	}
+
+	let ct = target_info::Iso14443b2ct{ uid: [0x01, 0x02, 0x03, 0x04], prod_code: 0, fab_code: 0 };
+	assert!(redact::reveal(|| ct.to_string()).contains("UID (decimal): 0067305985\n"));
+	let hashed = redact::with_policy(redact::Redaction::Hash(b"salt".to_vec()), || (ct.to_string(), redact::Redacted(&ct.uid).to_string()));
+	assert!(hashed.0.contains(&format!("UID (decimal): {}\n", hashed.1)));
+}
+
+#[test]
+fn card_id() {
+	use std::collections::HashSet;
+
+	let mut a = Target::new_iso14443a();
+	if let target_info::TargetInfo::Iso14443a(info) = &mut a.target_info {
+		info.uid[..7].copy_from_slice(&[0x04, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf6]);
+		info.uid_len = 7;
+		info.sak = 0x08;
+	}
+	let mut random = Target::new_iso14443a();
+	if let target_info::TargetInfo::Iso14443a(info) = &mut random.target_info {
+		info.uid[..4].copy_from_slice(&[0x08, 0x12, 0x34, 0x56]);
+		info.uid_len = 4;
+	}
+	let mut jewel = Target::new_jewel();
+	if let target_info::TargetInfo::Jewel(info) = &mut jewel.target_info {
+		info.id = [0x08, 0x12, 0x34, 0x56];
+	}
+
+	let id = a.card_id();
+	assert_eq!(id.to_hex(), "04a1b2c3d4e5f6");
+	assert_eq!((id.uid_size(), id.cascade_levels(), id.is_random()), (Some(UidSize::Double), Some(2), false));
+	assert_eq!(id, CardId::new(ModulationType::Iso14443a, &[0x04, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf6]));
+	assert!(random.card_id().is_random());
+	assert!(!jewel.card_id().is_random());
+	assert_eq!(jewel.card_id().uid_size(), None);
+	assert_ne!(random.card_id(), jewel.card_id());
+	assert_eq!(redact::with_policy(redact::Redaction::Mask, || id.to_string()), "** ** ** ** ** ** **");
+
+	// Selecting the same card twice gives the same identifier, even if other fields differ
+	let mut again = a;
+	if let target_info::TargetInfo::Iso14443a(info) = &mut again.target_info {
+		info.ats_len = 1;
+	}
+	let ids: HashSet<CardId> = [&a, &again, &random, &jewel].into_iter().map(CardId::from).collect();
+	assert_eq!(ids.len(), 3);
+}
+
+#[test]
+fn target_info_builders() {
+	use target_info::{BuildError, Dep, Iso14443a};
+
+	let info = Iso14443a::builder().uid(&[0x04, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf6]).ats(&[0x75, 0x77, 0x81, 0x02, 0x80]).build().unwrap();
+	assert_eq!((info.atqa, info.sak), ([0x00, 0x44], 0x20));
+	assert_eq!(info.uid(), &[0x04, 0xa1, 0xb2, 0xc3, 0xd4, 0xe5, 0xf6]);
+	assert_eq!(info.ats(), &[0x75, 0x77, 0x81, 0x02, 0x80]);
+	let target = Target{ target_info: info.into(), ..Target::new_iso14443a() };
+	assert_eq!(target.card_id().uid_size(), Some(UidSize::Double));
+
+	let uid = [0x01, 0x02, 0x03, 0x04];
+	assert_eq!(Iso14443a::builder().build(), Err(BuildError::Missing("uid")));
+	assert_eq!(Iso14443a::builder().uid(&uid[..3]).build(), Err(BuildError::InvalidUidLength(3)));
+	assert_eq!(Iso14443a::builder().uid(&uid).atqa([0x00, 0x44]).build(), Err(BuildError::AtqaUidSizeMismatch));
+	assert_eq!(Iso14443a::builder().uid(&uid).sak(0x24).build(), Err(BuildError::SakCascadeBit));
+	assert_eq!(Iso14443a::builder().uid(&uid).sak(0x08).ats(&[0x75]).build(), Err(BuildError::SakNotIso14443_4));
+	assert_eq!(Iso14443a::builder().uid(&uid).ats(&[0x75, 0x77]).build(), Err(BuildError::AtsTruncated));
+	assert_eq!(Iso14443a::builder().uid(&uid).ats(&[0u8; 255]).build(), Err(BuildError::TooLong{ field: "ats", len: 255, max: 254 }));
+
+	let dep = Dep::builder().nfcid3([0u8; 10]).gb(&[0x46, 0x66, 0x6d]).dep_mode(DepMode::Passive).build().unwrap();
+	assert_eq!((dep.gb(), dep.gb_len), (&[0x46, 0x66, 0x6d][..], 3));
+}
+
+#[test]
+fn capabilities() {
+	let modulation = |modulation_type, baud_rate| Modulation{ modulation_type, baud_rate };
+	let capabilities = Capabilities{
+		name: "ACS / ACR122U PICC Interface".to_string(),
+		connstring: "acr122_usb:001:004".to_string(),
+		driver: "acr122_usb".to_string(),
+		initiator: vec![
+			modulation(ModulationType::Iso14443a, BaudRate::Baud106),
+			modulation(ModulationType::Felica, BaudRate::Baud212),
+			modulation(ModulationType::Felica, BaudRate::Baud424),
+		],
+		target: vec![modulation(ModulationType::Iso14443a, BaudRate::Baud106)],
+	};
+	assert!(capabilities.supports(Mode::Initiator, &modulation(ModulationType::Felica, BaudRate::Baud424)));
+	assert!(!capabilities.supports(Mode::Target, &modulation(ModulationType::Felica, BaudRate::Baud424)));
+	assert!(capabilities.supports_modulation_type(Mode::Initiator, ModulationType::Felica));
+	assert_eq!(capabilities.missing(Mode::Initiator, &[
+		modulation(ModulationType::Iso14443a, BaudRate::Baud106),
+		modulation(ModulationType::Iso14443b, BaudRate::Baud106),
+	]), vec![modulation(ModulationType::Iso14443b, BaudRate::Baud106)]);
+}
+
+#[test]
+fn device_information() {
+	let information = DeviceInformation::parse("\
+chip: PN533 v2.7
+initator mode modulations: ISO/IEC 14443A (106 kbps), FeliCa (424 kbps, 212 kbps), ISO/IEC 14443-2B-3B iClass (Picopass) (106 kbps)
+target mode modulations: D.E.P. (424 kbps, 212 kbps, 106 kbps)
+");
+	assert_eq!(information.chip.as_deref(), Some("PN533"));
+	assert_eq!(information.firmware_version, Some(FirmwareVersion{ major: 2, minor: 7 }));
+	let modulation = |modulation_type, baud_rate| Modulation{ modulation_type, baud_rate };
+	assert_eq!(information.initiator_modulations, vec![
+		modulation(ModulationType::Iso14443a, BaudRate::Baud106),
+		modulation(ModulationType::Felica, BaudRate::Baud424),
+		modulation(ModulationType::Felica, BaudRate::Baud212),
+		modulation(ModulationType::Iso14443biClass, BaudRate::Baud106),
+	]);
+	assert_eq!(information.target_modulations.len(), 3);
+
+	let unknown = DeviceInformation::parse("ACS ACR122U PICC Interface\n");
+	assert_eq!((unknown.chip, unknown.initiator_modulations.len()), (None, 0));
+	assert_eq!(unknown.raw, "ACS ACR122U PICC Interface\n");
+}
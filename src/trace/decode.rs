@@ -0,0 +1,442 @@
+//! Annotation of captured frames, similar to the `trace list` command of the Proxmark3 client.
+//!
+//! The decoder follows the exchange between initiator and target, so that
+//! responses are annotated according to the command they answer. It knows the
+//! ISO 14443-3 activation (REQA, ANTICOLL, SELECT, ...), ISO 14443-4 (RATS, PPS,
+//! I/R/S blocks and the APDUs they carry), MIFARE Classic and Ultralight
+//! commands, ISO 14443B activation and FeliCa commands.
+
+use super::{Direction, Frame};
+use crate::ModulationType;
+use crate::redact::Redacted;
+use std::fmt;
+
+/// Outcome of checking the CRC at the end of a frame
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CrcStatus {
+	/// The frame has no CRC, or it can not be checked (e.g. because the frame is encrypted)
+	Absent,
+	Valid,
+	Invalid,
+}
+
+/// Description of a single frame
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Annotation {
+	pub description: String,
+	pub crc: CrcStatus,
+	/// Indices of the bytes whose parity bit is wrong, if parity is known
+	pub parity_errors: Vec<usize>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Command {
+	None,
+	Reqa,
+	Anticoll,
+	Select,
+	Rats,
+	Pps,
+	Auth,
+	AuthReader,
+	Read,
+	Write,
+	GetVersion,
+	ReadSignature,
+	Reqb,
+	Attrib,
+	IsoDep,
+	Other,
+}
+
+/// Annotates frames one after the other, see the module documentation
+#[derive(Debug, Clone)]
+pub struct Decoder {
+	last: Command,
+	iso_dep: bool,
+	encrypted: bool,
+}
+
+impl Default for Decoder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+// Same polynomial as ISO 14443, in the (non-reflected) form FeliCa uses
+fn felica_crc(data: &[u8]) -> u16 {
+	let mut crc = 0u16;
+	for byte in data {
+		crc ^= (*byte as u16) << 8;
+		for _ in 0..8 {
+			crc = if crc & 0x8000 != 0 { (crc << 1) ^ 0x1021 } else { crc << 1 };
+		}
+	}
+	crc
+}
+
+fn check_crc(modulation_type: ModulationType, data: &[u8]) -> CrcStatus {
+	if data.len() < 3 {
+		return CrcStatus::Absent;
+	}
+	let (payload, crc) = data.split_at(data.len() - 2);
+	let expected = match modulation_type {
+		ModulationType::Felica => felica_crc(payload).to_be_bytes(),
+		ModulationType::Iso14443b | ModulationType::Iso14443bi | ModulationType::Iso14443b2sr | ModulationType::Iso14443b2ct | ModulationType::Iso14443biClass => crate::crc::crc_b(payload),
+		_ => crate::crc::crc_a(payload),
+	};
+	if crc == expected { CrcStatus::Valid } else { CrcStatus::Invalid }
+}
+
+fn parity_errors(frame: &Frame) -> Vec<usize> {
+	let Some(parity) = &frame.parity else { return vec![] };
+	frame.data.iter().zip(parity.iter()).enumerate()
+		.filter(|(_, (byte, parity))| **parity & 1 != crate::crc::odd_parity(**byte))
+		.map(|(i, _)| i)
+		.collect()
+}
+
+fn apdu_command(apdu: &[u8]) -> String {
+	if apdu.len() < 4 {
+		return "APDU".to_string();
+	}
+	let (cla, ins, p1) = (apdu[0], apdu[1], apdu[2]);
+	if cla == 0x90 {
+		let name = match ins {
+			0x0a => "AUTHENTICATE",
+			0x1a => "AUTHENTICATE ISO",
+			0xaa => "AUTHENTICATE AES",
+			0x3d => "WRITE DATA",
+			0x5a => "SELECT APPLICATION",
+			0x60 => "GET VERSION",
+			0x6a => "GET APPLICATION IDS",
+			0x6f => "GET FILE IDS",
+			0xaf => "ADDITIONAL FRAME",
+			0xbd => "READ DATA",
+			_ => return format!("DESFire {:02x}", ins),
+		};
+		return format!("DESFire {}", name);
+	}
+	let name = match ins {
+		0x20 => "VERIFY",
+		0x82 => "EXTERNAL AUTHENTICATE",
+		0x84 => "GET CHALLENGE",
+		0x88 => "INTERNAL AUTHENTICATE",
+		0xa4 if p1 == 0x04 => "SELECT (by name)",
+		0xa4 => "SELECT",
+		0xb0 => "READ BINARY",
+		0xb2 => "READ RECORD",
+		0xc0 => "GET RESPONSE",
+		0xca => "GET DATA",
+		0xd6 => "UPDATE BINARY",
+		0xda => "PUT DATA",
+		0xdc => "UPDATE RECORD",
+		_ => return format!("APDU INS {:02x}", ins),
+	};
+	name.to_string()
+}
+
+fn status_word(sw1: u8, sw2: u8) -> String {
+	let meaning = match (sw1, sw2) {
+		(0x90, 0x00) | (0x91, 0x00) => "OK".to_string(),
+		(0x91, 0xaf) => "additional frame".to_string(),
+		(0x91, 0xae) => "authentication error".to_string(),
+		(0x61, n) => format!("{} bytes available", n),
+		(0x62, 0x83) => "selected file invalidated".to_string(),
+		(0x63, n) if n & 0xf0 == 0xc0 => format!("verification failed, {} retries left", n & 0x0f),
+		(0x67, 0x00) => "wrong length".to_string(),
+		(0x69, 0x82) => "security status not satisfied".to_string(),
+		(0x69, 0x85) => "conditions of use not satisfied".to_string(),
+		(0x6a, 0x82) => "file not found".to_string(),
+		(0x6a, 0x86) => "incorrect P1/P2".to_string(),
+		(0x6c, n) => format!("wrong Le, {} bytes available", n),
+		(0x6d, 0x00) => "INS not supported".to_string(),
+		(0x6e, 0x00) => "CLA not supported".to_string(),
+		_ => return format!("SW {:02x}{:02x}", sw1, sw2),
+	};
+	format!("SW {:02x}{:02x}: {}", sw1, sw2, meaning)
+}
+
+fn felica_command(code: u8) -> Option<&'static str> {
+	Some(match code & 0xfe {
+		0x00 => "POLLING",
+		0x02 => "REQUEST SERVICE",
+		0x04 => "REQUEST RESPONSE",
+		0x06 => "READ WITHOUT ENCRYPTION",
+		0x08 => "WRITE WITHOUT ENCRYPTION",
+		0x0a => "SEARCH SERVICE CODE",
+		0x0c => "REQUEST SYSTEM CODE",
+		0x10 => "AUTHENTICATION1",
+		0x12 => "AUTHENTICATION2",
+		0x14 => "READ",
+		0x16 => "WRITE",
+		_ => return None,
+	})
+}
+
+impl Decoder {
+	pub fn new() -> Self {
+		Self{ last: Command::None, iso_dep: false, encrypted: false }
+	}
+
+	/// Annotates the next frame of the exchange
+	pub fn decode(&mut self, frame: &Frame) -> Annotation {
+		let modulation_type = frame.modulation.map_or(ModulationType::Iso14443a, |modulation| modulation.modulation_type);
+		// Parity bits are encrypted along with the data
+		let parity_errors = if self.encrypted { vec![] } else { parity_errors(frame) };
+		let crc = if self.has_crc(frame, modulation_type) { check_crc(modulation_type, &frame.data) } else { CrcStatus::Absent };
+		let payload = match crc {
+			CrcStatus::Absent => &frame.data[..],
+			_ => &frame.data[..frame.data.len() - 2],
+		};
+
+		let description = match modulation_type {
+			ModulationType::Felica => self.felica(frame.direction, payload),
+			ModulationType::Iso14443b => self.iso14443b(frame.direction, payload),
+			_ => match frame.direction {
+				Direction::InitiatorToTarget => self.iso14443a_command(payload, frame.bits),
+				Direction::TargetToInitiator => self.iso14443a_response(payload, frame.bits),
+			},
+		};
+
+		Annotation{ description, crc, parity_errors }
+	}
+
+	// Short frames, anticollision frames and encrypted frames have no CRC which can be checked
+	fn has_crc(&self, frame: &Frame, modulation_type: ModulationType) -> bool {
+		if !frame.has_crc || frame.bits.is_some_and(|bits| bits % 8 != 0) {
+			return false;
+		}
+		if modulation_type != ModulationType::Iso14443a {
+			return true;
+		}
+		match frame.direction {
+			Direction::InitiatorToTarget => !self.encrypted && !matches!(frame.data[..], [0x93 | 0x95 | 0x97, nvb, ..] if nvb != 0x70),
+			Direction::TargetToInitiator => !self.encrypted && self.last != Command::Anticoll,
+		}
+	}
+
+	fn iso14443a_command(&mut self, data: &[u8], bits: Option<usize>) -> String {
+		if data.is_empty() {
+			return String::new();
+		}
+		if bits == Some(7) {
+			self.iso_dep = false;
+			self.encrypted = false;
+			self.last = Command::Reqa;
+			return match data[0] {
+				0x26 => "REQA",
+				0x52 => "WUPA",
+				0x40 => "MAGIC WUPC1",
+				0x43 => "MAGIC WUPC2",
+				_ => { self.last = Command::Other; "?" },
+			}.to_string();
+		}
+		if self.encrypted {
+			self.last = if self.last == Command::Auth { Command::AuthReader } else { Command::Other };
+			return "encrypted".to_string();
+		}
+		if self.iso_dep {
+			return self.iso_dep_block(Direction::InitiatorToTarget, data);
+		}
+
+		let cascade = match data[0] {
+			0x93 => "",
+			0x95 => "-2",
+			0x97 => "-3",
+			_ => "",
+		};
+		let block = data.get(1).copied().unwrap_or_default();
+		let (command, description) = match (data[0], data.len()) {
+			(0x93 | 0x95 | 0x97, _) if block == 0x70 => (Command::Select, format!("SELECT_UID{}", cascade)),
+			(0x93 | 0x95 | 0x97, _) => (Command::Anticoll, format!("ANTICOLL{}", cascade)),
+			(0x50, 2) if block == 0x00 => (Command::Other, "HALT".to_string()),
+			(0xe0, _) => (Command::Rats, "RATS".to_string()),
+			(0xd0..=0xdf, _) => (Command::Pps, "PPS".to_string()),
+			(0x60, 1) => (Command::GetVersion, "GET_VERSION".to_string()),
+			(0x60, _) => (Command::Auth, format!("AUTH-A({})", block)),
+			(0x61, _) => (Command::Auth, format!("AUTH-B({})", block)),
+			(0x30, _) => (Command::Read, format!("READBLOCK({})", block)),
+			(0xa0, _) => (Command::Write, format!("WRITEBLOCK({})", block)),
+			(0xa2, _) => (Command::Write, format!("WRITE({})", block)),
+			(0xc0, _) => (Command::Write, format!("DECREMENT({})", block)),
+			(0xc1, _) => (Command::Write, format!("INCREMENT({})", block)),
+			(0xc2, _) => (Command::Write, format!("RESTORE({})", block)),
+			(0xb0, _) => (Command::Write, format!("TRANSFER({})", block)),
+			(0x3a, _) => (Command::Read, format!("FAST_READ({}-{})", block, data.get(2).copied().unwrap_or_default())),
+			(0x1b, _) => (Command::Other, "PWD_AUTH".to_string()),
+			(0x39, _) => (Command::Other, format!("READ_CNT({})", block)),
+			(0x3c, _) => (Command::ReadSignature, "READ_SIG".to_string()),
+			(0xa5, _) => (Command::Write, format!("INCR_CNT({})", block)),
+			(0x3e, _) => (Command::Other, format!("CHECK_TEARING_EVENT({})", block)),
+			(0x4b, _) => (Command::Other, "VCSL".to_string()),
+			(0x1a, _) => (Command::Other, "AUTH-UL-C".to_string()),
+			(0xaf, _) => (Command::Other, "ADDITIONAL FRAME".to_string()),
+			_ => (Command::Other, "?".to_string()),
+		};
+		self.last = command;
+		description
+	}
+
+	fn iso14443a_response(&mut self, data: &[u8], bits: Option<usize>) -> String {
+		let last = std::mem::replace(&mut self.last, Command::None);
+		if bits == Some(4) {
+			return match data.first().map(|ack| ack & 0x0f) {
+				Some(0x0a) => "ACK",
+				_ => "NAK",
+			}.to_string();
+		}
+		if self.encrypted {
+			return "encrypted".to_string();
+		}
+		match last {
+			Command::Reqa => "ATQA".to_string(),
+			Command::Anticoll => match data {
+				[uid @ .., bcc] if data.len() == 5 && uid.iter().fold(0, |acc, byte| acc ^ byte) == *bcc => "UID, BCC ok".to_string(),
+				_ if data.len() == 5 => "UID, BCC invalid".to_string(),
+				_ => "UID (partial)".to_string(),
+			},
+			Command::Select => match data.first() {
+				Some(sak) if sak & 0x04 != 0 => "SAK (UID not complete)".to_string(),
+				Some(sak) if sak & 0x20 != 0 => "SAK (ISO 14443-4)".to_string(),
+				_ => "SAK".to_string(),
+			},
+			Command::Rats => {
+				self.iso_dep = true;
+				"ATS".to_string()
+			},
+			Command::Pps => "PPS response".to_string(),
+			Command::Auth if data.len() == 4 => {
+				self.encrypted = true;
+				self.last = Command::Auth;
+				"AUTH nonce (Nt)".to_string()
+			},
+			Command::Read => "data".to_string(),
+			Command::GetVersion => "version".to_string(),
+			Command::ReadSignature => "signature".to_string(),
+			_ if self.iso_dep => self.iso_dep_block(Direction::TargetToInitiator, data),
+			_ => String::new(),
+		}
+	}
+
+	fn iso_dep_block(&mut self, direction: Direction, data: &[u8]) -> String {
+		let Some(&pcb) = data.first() else { return String::new() };
+		self.last = Command::IsoDep;
+		if pcb & 0xe2 == 0x02 {
+			let mut inf = &data[1..];
+			if pcb & 0x08 != 0 {
+				inf = inf.get(1..).unwrap_or_default();
+			}
+			if pcb & 0x04 != 0 {
+				inf = inf.get(1..).unwrap_or_default();
+			}
+			let block = format!("I-block({})", pcb & 0x01);
+			if pcb & 0x10 != 0 {
+				return format!("{} chaining", block);
+			}
+			return match (direction, inf) {
+				(Direction::InitiatorToTarget, apdu) => format!("{} {}", block, apdu_command(apdu)),
+				(Direction::TargetToInitiator, [.., sw1, sw2]) => format!("{} {}", block, status_word(*sw1, *sw2)),
+				(Direction::TargetToInitiator, _) => block,
+			};
+		}
+		if pcb & 0xe6 == 0xa2 {
+			let kind = if pcb & 0x10 != 0 { "NAK" } else { "ACK" };
+			return format!("R-block({}) {}", pcb & 0x01, kind);
+		}
+		if pcb & 0xc7 == 0xc2 {
+			return match pcb & 0x30 {
+				0x00 => {
+					if direction == Direction::TargetToInitiator {
+						self.iso_dep = false;
+						self.last = Command::None;
+					}
+					"S-block DESELECT".to_string()
+				},
+				0x30 => "S-block WTX".to_string(),
+				_ => "S-block".to_string(),
+			};
+		}
+		"?".to_string()
+	}
+
+	fn iso14443b(&mut self, direction: Direction, data: &[u8]) -> String {
+		if self.iso_dep {
+			return self.iso_dep_block(direction, data);
+		}
+		match direction {
+			Direction::InitiatorToTarget => {
+				let (command, description) = match data.first() {
+					Some(0x05) if data.get(2).is_some_and(|param| param & 0x08 != 0) => (Command::Reqb, "WUPB"),
+					Some(0x05) => (Command::Reqb, "REQB"),
+					Some(0x1d) => (Command::Attrib, "ATTRIB"),
+					Some(0x50) => (Command::Other, "HLTB"),
+					_ => (Command::Other, "?"),
+				};
+				self.last = command;
+				description.to_string()
+			},
+			Direction::TargetToInitiator => match std::mem::replace(&mut self.last, Command::None) {
+				Command::Reqb => "ATQB".to_string(),
+				Command::Attrib => {
+					self.iso_dep = true;
+					"ATTRIB response".to_string()
+				},
+				_ => String::new(),
+			},
+		}
+	}
+
+	fn felica(&mut self, direction: Direction, data: &[u8]) -> String {
+		// FeliCa frames start with their length, followed by the command code
+		let Some(&code) = data.get(1) else { return String::new() };
+		match (felica_command(code), direction) {
+			(Some(name), Direction::InitiatorToTarget) if code & 0x01 == 0 => name.to_string(),
+			(Some(name), Direction::TargetToInitiator) if code & 0x01 != 0 => format!("{} response", name),
+			_ => "?".to_string(),
+		}
+	}
+}
+
+/// Annotates a sequence of frames
+pub fn annotate(frames: &[Frame]) -> Vec<Annotation> {
+	let mut decoder = Decoder::new();
+	frames.iter().map(|frame| decoder.decode(frame)).collect()
+}
+
+/// Formats frames as a table like the Proxmark3 `trace list` command.
+///
+/// Data is formatted through `Redacted`, so it is masked unless revealed, see `crate::redact`.
+pub struct Listing<'a>(pub &'a [Frame]);
+
+impl fmt::Display for Listing<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "{:>10} | {:>3} | {:<48} | {:<4} | Annotation", "Start (us)", "Src", "Data (! denotes parity error)", "CRC")?;
+		writeln!(f, "{}", "-".repeat(90))?;
+		let start = self.0.first().map(|frame| frame.timestamp);
+		for (frame, annotation) in self.0.iter().zip(annotate(self.0)) {
+			let offset = start.and_then(|start| frame.timestamp.duration_since(start).ok()).unwrap_or_default();
+			let source = match frame.direction {
+				Direction::InitiatorToTarget => "Rdr",
+				Direction::TargetToInitiator => "Tag",
+			};
+			let mut data = Redacted(&frame.data).to_string();
+			// Parity errors can only be marked when every byte is shown, rather than a hash
+			if data.len() + 1 == frame.data.len() * 3 {
+				for i in annotation.parity_errors.iter().rev() {
+					data.insert(i * 3 + 2, '!');
+				}
+			}
+			if let Some(bits) = frame.bits.filter(|bits| bits % 8 != 0) {
+				data.push_str(&format!("({})", bits));
+			}
+			let crc = match annotation.crc {
+				CrcStatus::Absent => "",
+				CrcStatus::Valid => "ok",
+				CrcStatus::Invalid => "!crc",
+			};
+			writeln!(f, "{:>10} | {:>3} | {:<48} | {:<4} | {}", offset.as_micros(), source, data, crc, annotation.description)?;
+		}
+		Ok(())
+	}
+}
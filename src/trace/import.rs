@@ -0,0 +1,284 @@
+//! Conversion of traces recorded by other tools into frames.
+//!
+//! Neither format records which modulation was used, so it has to be given.
+//! Imported frames can be annotated with `decode`, exported with `pcapng`, or
+//! replayed.
+
+use super::{Direction, Frame};
+use crate::{Modulation, ModulationType};
+use crate::crc::odd_parity;
+use std::fmt;
+use std::time::{Duration, SystemTime};
+
+/// Carrier frequency, the unit of Proxmark3 timestamps
+const CARRIER_HZ: u64 = 13_560_000;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ImportErrorKind {
+	/// The trace ends in the middle of a record
+	Truncated,
+	InvalidHex(String),
+	/// A PN53x frame with a wrong length (LCS) or data (DCS) checksum
+	InvalidChecksum,
+	/// Frames of a driver which does not talk to the PN53x with its own framing, such as `libnfc.driver.acr122_usb`
+	UnsupportedDriver(String),
+	/// `TX:`/`RX:` lines were found, but none of them carried a PN53x frame
+	NoPn53xFrames,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ImportError {
+	pub kind: ImportErrorKind,
+	/// Byte offset of the record the error was found in, for binary traces
+	pub offset: Option<usize>,
+	/// 1-based line number the error was found on, for text traces
+	pub line: Option<usize>,
+}
+
+impl fmt::Display for ImportErrorKind {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ImportErrorKind::Truncated => write!(f, "Trace ends in the middle of a record"),
+			ImportErrorKind::InvalidHex(value) => write!(f, "Invalid hex byte: {}", value),
+			ImportErrorKind::InvalidChecksum => write!(f, "Invalid PN53x frame checksum"),
+			ImportErrorKind::UnsupportedDriver(category) => write!(f, "Unsupported driver framing: {}", category),
+			ImportErrorKind::NoPn53xFrames => write!(f, "No PN53x frames found"),
+		}
+	}
+}
+
+impl fmt::Display for ImportError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match (self.offset, self.line) {
+			(Some(offset), _) => write!(f, "offset {}: {}", offset, self.kind),
+			(_, Some(line)) => write!(f, "line {}: {}", line, self.kind),
+			_ => write!(f, "{}", self.kind),
+		}
+	}
+}
+
+impl std::error::Error for ImportError {}
+
+// Neither format records the length of short frames, which are needed to tell
+// REQA, WUPA, ACK and NAK apart from other frames
+fn guess_bits(frame: &mut Frame) {
+	if frame.modulation.is_some_and(|modulation| modulation.modulation_type != ModulationType::Iso14443a) || frame.bits.is_some() {
+		return;
+	}
+	frame.bits = match (frame.direction, &frame.data[..]) {
+		(Direction::InitiatorToTarget, [0x26 | 0x52 | 0x40 | 0x43]) => Some(7),
+		(Direction::TargetToInitiator, [nibble]) if *nibble < 0x10 => Some(4),
+		_ => None,
+	};
+}
+
+/// Imports a binary Proxmark3 trace, as written by `trace save`.
+///
+/// Each record holds a timestamp in carrier periods (1/13.56 MHz), a duration,
+/// the data length with the response flag in its top bit, the data and its
+/// parity bits. Timestamps are converted relative to `start`.
+pub fn proxmark3(data: &[u8], modulation: Modulation, start: SystemTime) -> Result<Vec<Frame>, ImportError> {
+	let mut frames = vec![];
+	let mut offset = 0;
+	while offset < data.len() {
+		let truncated = || ImportError{ kind: ImportErrorKind::Truncated, offset: Some(offset), line: None };
+		let header = data.get(offset..offset + 8).ok_or_else(truncated)?;
+		let timestamp = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+		let len_flags = u16::from_le_bytes([header[6], header[7]]);
+		let len = (len_flags & 0x7fff) as usize;
+		let parity_len = if len == 0 { 0 } else { (len - 1) / 8 + 1 };
+		let record = data.get(offset + 8..offset + 8 + len + parity_len).ok_or_else(truncated)?;
+		let (bytes, parity) = record.split_at(len);
+
+		let direction = if len_flags & 0x8000 != 0 { Direction::TargetToInitiator } else { Direction::InitiatorToTarget };
+		let mut frame = Frame::new(direction, bytes);
+		frame.modulation = Some(modulation);
+		frame.timestamp = start + Duration::from_nanos(timestamp as u64 * 1_000_000_000 / CARRIER_HZ);
+		// Parity bits are packed most significant bit first
+		frame.parity = Some((0..len).map(|i| (parity[i / 8] >> (7 - i % 8)) & 1).collect());
+		frame.has_crc = true;
+		guess_bits(&mut frame);
+		frames.push(frame);
+
+		offset += 8 + len + parity_len;
+	}
+	Ok(frames)
+}
+
+fn parse_hex(tokens: &[&str], line: usize) -> Result<Vec<u8>, ImportError> {
+	tokens.iter()
+		.map(|token| u8::from_str_radix(token, 16).map_err(|_| ImportError{ kind: ImportErrorKind::InvalidHex(token.to_string()), offset: None, line: Some(line) }))
+		.collect()
+}
+
+// PN53x register controlling the number of bits sent in the last byte
+const BIT_FRAMING_REGISTER: [u8; 2] = [0x63, 0x3d];
+
+const PN53X_START_CODE: [u8; 2] = [0x00, 0xff];
+
+// Drivers which wrap PN53x commands in CCID or APDUs rather than PN53x frames
+const WRAPPING_DRIVERS: [&str; 3] = ["libnfc.driver.acr122_usb", "libnfc.driver.acr122_pcsc", "libnfc.driver.pcsc"];
+
+fn checksum(data: &[u8]) -> u8 {
+	data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
+}
+
+// Takes the next PN53x information frame out of the bytes of one direction,
+// returning its TFI and data. ACK and NACK frames are skipped, as are bytes
+// before the start code (preamble, PN532 wake up). Returns `None` until a
+// whole frame has been received, as serial buses log each read separately.
+fn next_pn53x_frame(buffer: &mut Vec<u8>) -> Result<Option<Vec<u8>>, ImportErrorKind> {
+	loop {
+		let Some(start) = buffer.windows(2).position(|window| window == PN53X_START_CODE) else {
+			// Keep a 00 which may be followed by the rest of the start code
+			let keep = usize::from(buffer.last() == Some(&0x00));
+			buffer.drain(..buffer.len() - keep);
+			return Ok(None);
+		};
+		buffer.drain(..start);
+		let (len, header) = match buffer[2..] {
+			// ACK, NACK
+			[0x00, 0xff, ..] | [0xff, 0x00, ..] => {
+				buffer.drain(..4);
+				continue;
+			},
+			// Extended information frame: LENM, LENL, LCS
+			[0xff, 0xff, len_m, len_l, lcs, ..] => {
+				if checksum(&[len_m, len_l, lcs]) != 0 {
+					return Err(ImportErrorKind::InvalidChecksum);
+				}
+				(u16::from_be_bytes([len_m, len_l]) as usize, 7)
+			},
+			[0xff, 0xff, ..] => return Ok(None),
+			// Normal information frame: LEN, LCS
+			[len, lcs, ..] => {
+				if checksum(&[len, lcs]) != 0 {
+					return Err(ImportErrorKind::InvalidChecksum);
+				}
+				(len as usize, 4)
+			},
+			_ => return Ok(None),
+		};
+		// Data, then DCS
+		let Some(frame) = buffer.get(header..header + len + 1) else { return Ok(None) };
+		if checksum(frame) != 0 {
+			return Err(ImportErrorKind::InvalidChecksum);
+		}
+		let data = frame[..len].to_vec();
+		buffer.drain(..header + len + 1);
+		return Ok(Some(data));
+	}
+}
+
+/// Imports a libnfc debug log (`LIBNFC_LOG_LEVEL=3`), or the output of the libnfc example tools.
+///
+/// From debug logs, the `TX:`/`RX:` lines of the driver and bus layers
+/// (`libnfc.driver.*`, `libnfc.bus.*`) are used. The PN53x frames they carry
+/// are reassembled and their checksums checked, and frames sent and received
+/// with `InDataExchange`/`InCommunicateThru` in initiator mode and
+/// `TgGetData`/`TgSetData` and friends in target mode are kept. Drivers which
+/// wrap PN53x commands in CCID or APDUs (ACR122, PC/SC) are not supported.
+/// From the example tools, the `Sent bits:` and `Received bits:` lines are
+/// used, including bit counts and parity errors (`!`). Lines of other formats
+/// are ignored. All frames are timestamped with `start`.
+pub fn libnfc_log(log: &str, modulation: Modulation, start: SystemTime) -> Result<Vec<Frame>, ImportError> {
+	let mut frames = vec![];
+	let mut tx_last_bits = 0;
+	// Bytes sent and received which do not form a whole PN53x frame yet
+	let mut tx_buffer = vec![];
+	let mut rx_buffer = vec![];
+	// First line of the debug log with frame data, and whether any of them carried a PN53x frame
+	let mut first_frame_line = None;
+	let mut found_pn53x_frame = false;
+	for (i, line) in log.lines().enumerate() {
+		let line_number = i + 1;
+		let trimmed = line.trim();
+		let error = |kind| ImportError{ kind, offset: None, line: Some(line_number) };
+
+		let example = [("Sent bits:", Direction::InitiatorToTarget), ("Received bits:", Direction::TargetToInitiator)]
+			.into_iter()
+			.find_map(|(prefix, direction)| trimmed.strip_prefix(prefix).map(|rest| (direction, rest)));
+		if let Some((direction, rest)) = example {
+			let mut tokens: Vec<&str> = rest.split_whitespace().collect();
+			let mut last_bits = None;
+			if let Some(bits) = tokens.iter().position(|token| token.starts_with('(')) {
+				last_bits = tokens[bits].trim_start_matches('(').parse::<usize>().ok();
+				tokens.truncate(bits);
+			}
+			let errors: Vec<bool> = tokens.iter().map(|token| token.ends_with('!')).collect();
+			let tokens: Vec<&str> = tokens.iter().map(|token| token.trim_end_matches('!')).collect();
+			let data = parse_hex(&tokens, line_number)?;
+			if data.is_empty() {
+				continue;
+			}
+
+			let mut frame = Frame::new(direction, &data);
+			frame.modulation = Some(modulation);
+			frame.timestamp = start;
+			frame.bits = last_bits.map(|bits| (data.len() - 1) * 8 + bits);
+			if errors.contains(&true) {
+				frame.parity = Some(data.iter().zip(errors).map(|(byte, error)| odd_parity(*byte) ^ error as u8).collect());
+			}
+			frames.push(frame);
+			continue;
+		}
+
+		// "<priority>\t<category>\tTX: 00 00 ff ..." (see log_put and LOG_HEX in libnfc)
+		let mut parts = trimmed.splitn(3, '\t');
+		let (Some(_), Some(category), Some(message)) = (parts.next(), parts.next(), parts.next()) else { continue };
+		if !category.starts_with("libnfc.driver.") && !category.starts_with("libnfc.bus.") {
+			continue;
+		}
+		let (tx, rest) = match (message.strip_prefix("TX: "), message.strip_prefix("RX: ")) {
+			(Some(rest), _) => (true, rest),
+			(_, Some(rest)) => (false, rest),
+			_ => continue,
+		};
+		if WRAPPING_DRIVERS.contains(&category) {
+			return Err(error(ImportErrorKind::UnsupportedDriver(category.to_string())));
+		}
+		first_frame_line.get_or_insert(line_number);
+		let tokens: Vec<&str> = rest.split_whitespace().collect();
+		let buffer = if tx { &mut tx_buffer } else { &mut rx_buffer };
+		buffer.extend(parse_hex(&tokens, line_number)?);
+
+		while let Some(data) = next_pn53x_frame(buffer).map_err(error)? {
+			found_pn53x_frame = true;
+			let (direction, payload) = match (tx, &data[..]) {
+				// WriteRegister: track the bit framing of the next frame
+				(true, [0xd4, 0x08, registers @ ..]) => {
+					for register in registers.chunks_exact(3) {
+						if register[..2] == BIT_FRAMING_REGISTER {
+							tx_last_bits = (register[2] & 0x07) as usize;
+						}
+					}
+					continue;
+				},
+				// InDataExchange, InCommunicateThru
+				(true, [0xd4, 0x40, _, payload @ ..]) | (true, [0xd4, 0x42, payload @ ..]) => (Direction::InitiatorToTarget, payload),
+				(false, [0xd5, 0x41 | 0x43, status, payload @ ..]) if status & 0x3f == 0 => (Direction::TargetToInitiator, payload),
+				// TgSetData, TgSetMetaData, TgResponseToInitiator
+				(true, [0xd4, 0x8e | 0x94 | 0x90, payload @ ..]) => (Direction::TargetToInitiator, payload),
+				// TgGetData, TgGetInitiatorCommand
+				(false, [0xd5, 0x87 | 0x89, status, payload @ ..]) if status & 0x3f == 0 => (Direction::InitiatorToTarget, payload),
+				_ => continue,
+			};
+			if payload.is_empty() {
+				continue;
+			}
+
+			let mut frame = Frame::new(direction, payload);
+			frame.modulation = Some(modulation);
+			frame.timestamp = start;
+			if tx && tx_last_bits != 0 {
+				frame.bits = Some((payload.len() - 1) * 8 + tx_last_bits);
+			}
+			guess_bits(&mut frame);
+			frames.push(frame);
+		}
+	}
+	if let (Some(line), false) = (first_frame_line, found_pn53x_frame) {
+		return Err(ImportError{ kind: ImportErrorKind::NoPn53xFrames, offset: None, line: Some(line) });
+	}
+	Ok(frames)
+}
@@ -0,0 +1,87 @@
+//! Capture of the frames exchanged between an initiator and a target.
+//!
+//! A `Device` passes every frame it sends or receives to its trace sink, see
+//! `Device::set_trace_sink`. Trace sinks receive full, unredacted frames.
+
+use crate::Modulation;
+use crate::redact::Redacted;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+pub mod decode;
+pub mod import;
+pub mod pcapng;
+
+/// Which side of the exchange sent a frame
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Direction {
+	/// Sent by the initiator (PCD, reader) to the target (PICC, card)
+	InitiatorToTarget,
+	/// Sent by the target (PICC, card) to the initiator (PCD, reader)
+	TargetToInitiator,
+}
+
+/// A single frame exchanged between an initiator and a target
+#[derive(PartialEq, Eq, Clone)]
+pub struct Frame {
+	pub direction: Direction,
+	/// Modulation in use when the frame was exchanged, if known
+	pub modulation: Option<Modulation>,
+	pub timestamp: SystemTime,
+	pub data: Vec<u8>,
+	/// Number of valid bits in `data`, for bit-oriented frames
+	pub bits: Option<usize>,
+	/// Parity bit of each byte in `data` (one byte per byte, 0 or 1), if known
+	pub parity: Option<Vec<u8>>,
+	/// Whether `data` includes the CRC of the frame, rather than the CRC being handled by the reader chip
+	pub has_crc: bool,
+}
+
+impl Frame {
+	pub fn new(direction: Direction, data: &[u8]) -> Self {
+		Self{
+			direction,
+			modulation: None,
+			timestamp: SystemTime::now(),
+			data: data.to_vec(),
+			bits: None,
+			parity: None,
+			has_crc: false,
+		}
+	}
+}
+
+// Data and parity are formatted through `Redacted`, see `crate::redact`
+impl fmt::Debug for Frame {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Frame")
+			.field("direction", &self.direction)
+			.field("modulation", &self.modulation)
+			.field("timestamp", &self.timestamp)
+			.field("data", &Redacted(&self.data))
+			.field("bits", &self.bits)
+			.field("parity", &self.parity.as_deref().map(Redacted))
+			.field("has_crc", &self.has_crc)
+			.finish()
+	}
+}
+
+/// Receives frames as they are exchanged by a `Device`
+pub trait TraceSink: Send {
+	fn frame(&mut self, frame: &Frame);
+}
+
+/// Collects frames in memory
+impl TraceSink for Vec<Frame> {
+	fn frame(&mut self, frame: &Frame) {
+		self.push(frame.clone());
+	}
+}
+
+/// Allows keeping a handle to a sink installed on a `Device`
+impl<T: TraceSink> TraceSink for Arc<Mutex<T>> {
+	fn frame(&mut self, frame: &Frame) {
+		self.lock().unwrap_or_else(|err| err.into_inner()).frame(frame);
+	}
+}
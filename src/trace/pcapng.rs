@@ -0,0 +1,154 @@
+//! pcapng export of frames, for inspection in Wireshark.
+//!
+//! Frames are written with the ISO 14443 link type (`LINKTYPE_ISO_14443`),
+//! which Wireshark dissects as ISO 14443 and, for ISO-DEP, ISO 7816 traffic.
+//! Each packet starts with a 4 byte pseudo-header: version (0), event and the
+//! big-endian length of the frame. Modulation, bit count and parity, which the
+//! link type can not express, are stored in the packet comment.
+
+use super::{Direction, Frame, TraceSink};
+use std::io::{self, Write};
+use std::time::UNIX_EPOCH;
+
+/// `LINKTYPE_ISO_14443`
+pub const LINKTYPE_ISO_14443: u16 = 264;
+
+const BLOCK_SECTION_HEADER: u32 = 0x0A0D0D0A;
+const BLOCK_INTERFACE_DESCRIPTION: u32 = 0x00000001;
+const BLOCK_ENHANCED_PACKET: u32 = 0x00000006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B3C4D;
+
+const OPT_END: u16 = 0;
+const OPT_COMMENT: u16 = 1;
+const OPT_EPB_FLAGS: u16 = 2;
+
+const EPB_FLAGS_INBOUND: u32 = 0x01;
+const EPB_FLAGS_OUTBOUND: u32 = 0x02;
+
+const EVENT_DATA_PICC_TO_PCD: u8 = 0xFF;
+const EVENT_DATA_PCD_TO_PICC: u8 = 0xFE;
+const EVENT_DATA_PICC_TO_PCD_CRC_DROPPED: u8 = 0xFB;
+const EVENT_DATA_PCD_TO_PICC_CRC_DROPPED: u8 = 0xFA;
+
+fn pad(len: usize) -> usize {
+	(4 - len % 4) % 4
+}
+
+fn push_option(body: &mut Vec<u8>, code: u16, value: &[u8]) {
+	body.extend_from_slice(&code.to_le_bytes());
+	body.extend_from_slice(&(value.len() as u16).to_le_bytes());
+	body.extend_from_slice(value);
+	body.resize(body.len() + pad(value.len()), 0);
+}
+
+fn comment(frame: &Frame) -> String {
+	let mut parts = vec![];
+	if let Some(modulation) = &frame.modulation {
+		parts.push(modulation.to_string());
+	}
+	if let Some(bits) = frame.bits {
+		parts.push(format!("{} bits", bits));
+	}
+	if let Some(parity) = &frame.parity {
+		let parity: Vec<String> = parity.iter().map(|bit| (bit & 1).to_string()).collect();
+		parts.push(format!("parity {}", parity.join(" ")));
+	}
+	parts.join("; ")
+}
+
+/// Writes frames to a pcapng file, see the module documentation
+pub struct PcapngWriter<W: Write> {
+	writer: W,
+	error: Option<io::Error>,
+}
+
+impl<W: Write> PcapngWriter<W> {
+	/// Writes the section header and interface description blocks
+	pub fn new(writer: W) -> io::Result<Self> {
+		let mut pcapng = Self{ writer, error: None };
+
+		let mut section = vec![];
+		section.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+		section.extend_from_slice(&1u16.to_le_bytes());
+		section.extend_from_slice(&0u16.to_le_bytes());
+		section.extend_from_slice(&(-1i64).to_le_bytes());
+		pcapng.write_block(BLOCK_SECTION_HEADER, &section)?;
+
+		let mut interface = vec![];
+		interface.extend_from_slice(&LINKTYPE_ISO_14443.to_le_bytes());
+		interface.extend_from_slice(&0u16.to_le_bytes());
+		interface.extend_from_slice(&0u32.to_le_bytes());
+		pcapng.write_block(BLOCK_INTERFACE_DESCRIPTION, &interface)?;
+
+		Ok(pcapng)
+	}
+
+	fn write_block(&mut self, block_type: u32, body: &[u8]) -> io::Result<()> {
+		let len = (12 + body.len()) as u32;
+		self.writer.write_all(&block_type.to_le_bytes())?;
+		self.writer.write_all(&len.to_le_bytes())?;
+		self.writer.write_all(body)?;
+		self.writer.write_all(&len.to_le_bytes())
+	}
+
+	/// Writes a frame as an enhanced packet block
+	pub fn write_frame(&mut self, frame: &Frame) -> io::Result<()> {
+		let data = match frame.bits {
+			Some(bits) => &frame.data[..frame.data.len().min(bits.div_ceil(8))],
+			None => &frame.data[..],
+		};
+		let event = match (frame.direction, frame.has_crc) {
+			(Direction::InitiatorToTarget, true) => EVENT_DATA_PCD_TO_PICC,
+			(Direction::InitiatorToTarget, false) => EVENT_DATA_PCD_TO_PICC_CRC_DROPPED,
+			(Direction::TargetToInitiator, true) => EVENT_DATA_PICC_TO_PCD,
+			(Direction::TargetToInitiator, false) => EVENT_DATA_PICC_TO_PCD_CRC_DROPPED,
+		};
+		let mut packet = vec![0, event];
+		packet.extend_from_slice(&(data.len() as u16).to_be_bytes());
+		packet.extend_from_slice(data);
+
+		let timestamp = frame.timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_micros() as u64;
+		let mut body = vec![];
+		body.extend_from_slice(&0u32.to_le_bytes());
+		body.extend_from_slice(&((timestamp >> 32) as u32).to_le_bytes());
+		body.extend_from_slice(&(timestamp as u32).to_le_bytes());
+		body.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+		body.extend_from_slice(&(packet.len() as u32).to_le_bytes());
+		body.extend_from_slice(&packet);
+		body.resize(body.len() + pad(packet.len()), 0);
+
+		let flags = match frame.direction {
+			Direction::InitiatorToTarget => EPB_FLAGS_OUTBOUND,
+			Direction::TargetToInitiator => EPB_FLAGS_INBOUND,
+		};
+		push_option(&mut body, OPT_EPB_FLAGS, &flags.to_le_bytes());
+		let comment = comment(frame);
+		if !comment.is_empty() {
+			push_option(&mut body, OPT_COMMENT, comment.as_bytes());
+		}
+		push_option(&mut body, OPT_END, &[]);
+
+		self.write_block(BLOCK_ENHANCED_PACKET, &body)
+	}
+
+	/// Flushes and returns the underlying writer, or the first error which occurred while used as a `TraceSink`
+	pub fn finish(mut self) -> io::Result<W> {
+		if let Some(err) = self.error.take() {
+			return Err(err);
+		}
+		self.writer.flush()?;
+		Ok(self.writer)
+	}
+}
+
+/// Errors are kept and returned by `finish`, after which further frames are dropped
+impl<W: Write + Send> TraceSink for PcapngWriter<W> {
+	fn frame(&mut self, frame: &Frame) {
+		if self.error.is_some() {
+			return;
+		}
+		if let Err(err) = self.write_frame(frame) {
+			self.error = Some(err);
+		}
+	}
+}